@@ -16,6 +16,7 @@ struct State {
     input: String,
     load_button: ButtonState,
     paste_button: ButtonState,
+    drop_hovered: bool,
     download_state: Arc<Mutex<DownloadState>>,
 }
 
@@ -25,6 +26,7 @@ impl State {
             input: "".to_string(),
             load_button: ButtonState::default(),
             paste_button: ButtonState::default(),
+            drop_hovered: false,
             download_state: Arc::new(Mutex::new(DownloadState::Idle)),
         }
     }
@@ -119,93 +121,119 @@ fn main() {
             let download_state = state.download_state.blocking_lock().clone();
             let download_state_for_button = download_state.clone();
 
-            column_spaced(
-                20.,
-                vec![
-                    text(id!(), "Image Loader")
-                        .font_size(32)
-                        .font_weight(FontWeight::BOLD)
-                        .build(app.ctx())
-                        .pad(10.),
-                    row_spaced(
-                        10.,
-                        vec![
-                            text(id!(), {
-                                let input = state.input.clone();
-                                if input.len() > 20 {
-                                    format!("{}...", &input[..20])
-                                } else {
-                                    input
-                                }
-                            })
-                            .font_size(16)
-                            .view()
-                            .finish(app.ctx())
-                            .pad(10.)
-                            .width_range(..200.),
-                            button(id!(), binding!(state, State, paste_button))
-                                .label(|_state, ctx| text(id!(), "Paste").build(ctx))
-                                .on_click(|s, _| s.paste_from_clipboard())
-                                .build(app.ctx())
-                                .height(40.)
-                                .width(80.),
-                        ],
-                    ),
-                    button(id!(), binding!(state, State, load_button))
-                        .label(move |_state, ctx| {
-                            text(
-                                id!(),
-                                match download_state_for_button {
-                                    DownloadState::Downloading => "Loading...",
-                                    _ => "Load Image",
-                                },
-                            )
-                            .build(ctx)
-                        })
-                        .on_click(|s, app| {
-                            if matches!(
+            stack(vec![
+                rect(id!())
+                    .fill(if state.drop_hovered {
+                        Color::from_rgb8(40, 40, 48)
+                    } else {
+                        TRANSPARENT
+                    })
+                    .view()
+                    .on_file_hover(|s: &mut State, _, drag| {
+                        s.drop_hovered = matches!(drag, FileDragState::Hovered { .. })
+                    })
+                    .on_file_drop(|s: &mut State, app, paths, _| {
+                        if let Some(path) = paths.first() {
+                            s.input = path.display().to_string();
+                            if !matches!(
                                 s.download_state.blocking_lock().clone(),
                                 DownloadState::Downloading
                             ) {
-                                return;
+                                s.load_image(app);
                             }
-                            s.load_image(app);
-                        })
-                        .build(app.ctx())
-                        .height(50.)
-                        .width(200.),
-                    match download_state {
-                        DownloadState::Idle => {
-                            text(id!(), "Enter a URL or file path and click Load")
-                                .font_size(14)
-                                .build(app.ctx())
                         }
-                        DownloadState::Downloading => text(id!(), "Loading image...")
-                            .font_size(14)
-                            .build(app.ctx()),
-                        DownloadState::Success(ref image_id, ref bytes) => column_spaced(
+                    })
+                    .finish(app.ctx()),
+                column_spaced(
+                    20.,
+                    vec![
+                        text(id!(), "Image Loader")
+                            .font_size(32)
+                            .font_weight(FontWeight::BOLD)
+                            .build(app.ctx())
+                            .pad(10.),
+                        row_spaced(
                             10.,
                             vec![
-                                text(id!(), format!("Loaded {} bytes", bytes.len()))
-                                    .font_size(14)
-                                    .build(app.ctx()),
-                                image_from_bytes(id!(), bytes.clone())
-                                    .image_id(image_id)
-                                    .view()
-                                    .finish(app.ctx())
-                                    .height_range(100.0..)
-                                    .width_range(100.0..),
+                                text(id!(), {
+                                    let input = state.input.clone();
+                                    if input.len() > 20 {
+                                        format!("{}...", &input[..20])
+                                    } else {
+                                        input
+                                    }
+                                })
+                                .font_size(16)
+                                .view()
+                                .finish(app.ctx())
+                                .pad(10.)
+                                .width_range(..200.),
+                                button(id!(), binding!(state, State, paste_button))
+                                    .label(|_state, ctx| text(id!(), "Paste").build(ctx))
+                                    .on_click(|s, _| s.paste_from_clipboard())
+                                    .build(app.ctx())
+                                    .height(40.)
+                                    .width(80.),
                             ],
                         ),
-                        DownloadState::Error(ref error) => text(id!(), format!("Error: {error}"))
-                            .font_size(14)
-                            .fill(Color::from_rgb8(255, 0, 0))
-                            .build(app.ctx()),
-                    },
-                ],
-            )
-            .pad(20.)
-            .pad_top(20.)
+                        button(id!(), binding!(state, State, load_button))
+                            .label(move |_state, ctx| {
+                                text(
+                                    id!(),
+                                    match download_state_for_button {
+                                        DownloadState::Downloading => "Loading...",
+                                        _ => "Load Image",
+                                    },
+                                )
+                                .build(ctx)
+                            })
+                            .on_click(|s, app| {
+                                if matches!(
+                                    s.download_state.blocking_lock().clone(),
+                                    DownloadState::Downloading
+                                ) {
+                                    return;
+                                }
+                                s.load_image(app);
+                            })
+                            .build(app.ctx())
+                            .height(50.)
+                            .width(200.),
+                        match download_state {
+                            DownloadState::Idle => {
+                                text(id!(), "Paste a URL and click Load, or drop an image file")
+                                    .font_size(14)
+                                    .build(app.ctx())
+                            }
+                            DownloadState::Downloading => text(id!(), "Loading image...")
+                                .font_size(14)
+                                .build(app.ctx()),
+                            DownloadState::Success(ref image_id, ref bytes) => column_spaced(
+                                10.,
+                                vec![
+                                    text(id!(), format!("Loaded {} bytes", bytes.len()))
+                                        .font_size(14)
+                                        .build(app.ctx()),
+                                    image_from_bytes(id!(), bytes.clone())
+                                        .image_id(image_id)
+                                        .view()
+                                        .finish(app.ctx())
+                                        .height_range(100.0..)
+                                        .width_range(100.0..),
+                                ],
+                            ),
+                            DownloadState::Error(ref error) => {
+                                text(id!(), format!("Error: {error}"))
+                                    .font_size(14)
+                                    .fill(Color::from_rgb8(255, 0, 0))
+                                    .build(app.ctx())
+                            }
+                        },
+                    ],
+                )
+                .pad(20.)
+                .pad_top(20.),
+            ])
         }),
     )
     .start()
@@ -0,0 +1,345 @@
+use crate::Point;
+use crate::app::AppCtx;
+use backer::Area;
+use std::any::Any;
+use std::time::{Duration, Instant};
+use vello_svg::vello::peniko::Color;
+use vello_svg::vello::peniko::color::PremulColor;
+
+/// Longest step a spring takes at once, in seconds, so stiff springs stay stable.
+const SPRING_STEP: f32 = 1. / 240.;
+/// Longest frame step, so a stalled frame doesn't jump the spring.
+const MAX_FRAME_STEP: f32 = 0.1;
+/// A spring this close to rest, in progress and progress per second, has settled.
+const SPRING_REST: f32 = 0.001;
+
+/// A value that can be animated with `AppCtx::animate`.
+pub trait Animatable: Copy + 'static {
+    /// The value `t` of the way from `self` to `to`. Springs overshoot, so `t` can leave `0..=1`.
+    fn lerp(self, to: Self, t: f32) -> Self;
+    /// How far apart two values are, used to tell when the target has moved.
+    fn distance(self, other: Self) -> f32;
+}
+
+impl Animatable for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+
+    fn distance(self, other: Self) -> f32 {
+        (self - other).abs()
+    }
+}
+
+impl Animatable for Point {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Point::lerp(self, to, t as f64)
+    }
+
+    fn distance(self, other: Self) -> f32 {
+        Point::distance(self, other) as f32
+    }
+}
+
+impl Animatable for Area {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Area {
+            x: Animatable::lerp(self.x, to.x, t),
+            y: Animatable::lerp(self.y, to.y, t),
+            width: Animatable::lerp(self.width, to.width, t),
+            height: Animatable::lerp(self.height, to.height, t),
+        }
+    }
+
+    fn distance(self, other: Self) -> f32 {
+        self.x
+            .distance(other.x)
+            .max(self.y.distance(other.y))
+            .max(self.width.distance(other.width))
+            .max(self.height.distance(other.height))
+    }
+}
+
+impl Animatable for Color {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        // Premultiplied, so fading to or from transparent doesn't pass through other hues
+        let from = self.premultiply().components;
+        let to = to.premultiply().components;
+        PremulColor::new(std::array::from_fn(|i| Animatable::lerp(from[i], to[i], t)))
+            .un_premultiply()
+    }
+
+    fn distance(self, other: Self) -> f32 {
+        (0..4)
+            .map(|i| self.components[i].distance(other.components[i]))
+            .fold(0., f32::max)
+    }
+}
+
+/// The curve a timed transition follows.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    #[default]
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1. - (1. - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (2. - 2. * t).powi(3) * 0.5
+                }
+            }
+        }
+    }
+}
+
+/// How an animated value moves to a new target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    /// Reaches the target after `duration`, following `easing`.
+    Timed { duration: Duration, easing: Easing },
+    /// A spring pulling the value to the target. Lower damping bounces more, a damping of
+    /// `2 * stiffness.sqrt()` settles as fast as possible without overshooting.
+    Spring { stiffness: f32, damping: f32 },
+}
+
+impl Transition {
+    pub fn timed(duration: Duration, easing: Easing) -> Self {
+        Transition::Timed { duration, easing }
+    }
+
+    pub fn spring(stiffness: f32, damping: f32) -> Self {
+        Transition::Spring { stiffness, damping }
+    }
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Transition::timed(Duration::from_millis(150), Easing::EaseOut)
+    }
+}
+
+struct Animation<T> {
+    from: T,
+    to: T,
+    transition: Transition,
+    started: Instant,
+    last_frame: Instant,
+    /// How far the value has come from `from` to `to`, before easing.
+    progress: f32,
+    /// Progress per second of a spring.
+    velocity: f32,
+}
+
+impl<T: Animatable> Animation<T> {
+    fn value(&self) -> T {
+        let t = match self.transition {
+            Transition::Timed { easing, .. } => easing.apply(self.progress),
+            Transition::Spring { .. } => self.progress,
+        };
+        self.from.lerp(self.to, t)
+    }
+
+    fn settled(&self) -> bool {
+        self.progress == 1. && self.velocity == 0.
+    }
+
+    fn step(&mut self, now: Instant) {
+        match self.transition {
+            Transition::Timed { duration, .. } => {
+                self.progress = if duration.is_zero() {
+                    1.
+                } else {
+                    (now.saturating_duration_since(self.started).as_secs_f32()
+                        / duration.as_secs_f32())
+                    .min(1.)
+                };
+            }
+            Transition::Spring { stiffness, damping } => {
+                let mut remaining = now
+                    .saturating_duration_since(self.last_frame)
+                    .as_secs_f32()
+                    .min(MAX_FRAME_STEP);
+                while remaining > 0. {
+                    let dt = remaining.min(SPRING_STEP);
+                    self.velocity +=
+                        (stiffness * (1. - self.progress) - damping * self.velocity) * dt;
+                    self.progress += self.velocity * dt;
+                    remaining -= dt;
+                }
+                if (1. - self.progress).abs() < SPRING_REST && self.velocity.abs() < SPRING_REST {
+                    self.progress = 1.;
+                    self.velocity = 0.;
+                }
+            }
+        }
+        self.last_frame = now;
+    }
+
+    /// Starts moving from the current value to `target`. A spring keeps its speed, so
+    /// changing the target mid-flight doesn't stop it dead.
+    fn retarget(&mut self, target: T, now: Instant) {
+        let current = self.value();
+        let span = current.distance(target);
+        self.velocity = match self.transition {
+            Transition::Spring { .. } if span > 0. => {
+                // Where the old motion would be a step from now, measured along the new span
+                let ahead = self
+                    .from
+                    .lerp(self.to, self.progress + self.velocity * SPRING_STEP);
+                (1. - ahead.distance(target) / span) / SPRING_STEP
+            }
+            _ => 0.,
+        };
+        self.from = current;
+        self.to = target;
+        self.started = now;
+        self.last_frame = now;
+        self.progress = 0.;
+    }
+}
+
+impl AppCtx {
+    /// The value of the animation with `id` for this frame, moving towards `target` with
+    /// `transition`. The first call for an `id` starts at `target`. Another frame is requested
+    /// while the value is still moving, and the animation is dropped after a frame that
+    /// doesn't read it.
+    pub fn animate<T: Animatable>(&mut self, id: u64, target: T, transition: Transition) -> T {
+        let now = self.frame_time;
+        self.animations_used.insert(id);
+        let animation = self
+            .animations
+            .get_mut(&id)
+            .and_then(|animation| animation.downcast_mut::<Animation<T>>());
+        let Some(animation) = animation else {
+            let animation: Box<dyn Any> = Box::new(Animation {
+                from: target,
+                to: target,
+                transition,
+                started: now,
+                last_frame: now,
+                progress: 1.,
+                velocity: 0.,
+            });
+            self.animations.insert(id, animation);
+            return target;
+        };
+        animation.transition = transition;
+        animation.step(now);
+        if target.distance(animation.to) > 0. {
+            animation.retarget(target, now);
+        }
+        if !animation.settled() {
+            self.frame_requested = true;
+        }
+        animation.value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppState, Layout, TestApp, View, space};
+
+    #[test]
+    fn easings_start_and_end_in_place() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.), 0.);
+            assert_eq!(easing.apply(1.), 1.);
+            let mut last = 0.;
+            for step in 1..=20 {
+                let t = easing.apply(step as f32 / 20.);
+                assert!(t >= last, "{easing:?} goes backwards at step {step}");
+                last = t;
+            }
+        }
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+    }
+
+    #[test]
+    fn colors_lerp_through_premultiplied_alpha() {
+        let from = Color::from_rgba8(255, 0, 0, 255);
+        let to = Color::from_rgba8(0, 0, 255, 0);
+        let mid = from.lerp(to, 0.5);
+        assert!((mid.components[3] - 0.5).abs() < 0.001);
+        // Fading to transparent keeps the hue instead of passing through blue
+        assert!((mid.components[0] - 1.).abs() < 0.001);
+        assert!(mid.components[2].abs() < 0.001);
+    }
+
+    #[derive(Default)]
+    struct State {
+        animating: bool,
+    }
+
+    fn view<'a>(state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        if state.animating {
+            app.ctx().animate(1, 1f32, Transition::default());
+        }
+        space()
+    }
+
+    #[test]
+    fn unused_animations_are_dropped() {
+        let mut app = TestApp::new(State { animating: true }, view);
+        app.layout();
+        assert!(app.app_state().app_context.animations.contains_key(&1));
+        app.state.animating = false;
+        app.layout();
+        assert!(app.app_state().app_context.animations.is_empty());
+    }
+
+    fn spring_frame(ctx: &mut AppCtx, now: Instant, target: f32) -> (f32, f32) {
+        ctx.begin_frame(now);
+        let value = ctx.animate(2, target, Transition::spring(170., 26.));
+        let animation = ctx.animations[&2].downcast_ref::<Animation<f32>>().unwrap();
+        // In logical units per second, rather than progress per second
+        let speed = animation.velocity * animation.from.distance(animation.to);
+        (value, speed)
+    }
+
+    #[test]
+    fn retargeting_a_spring_keeps_its_velocity() {
+        let mut app = TestApp::new(State::default(), view);
+        let ctx = &mut app.app_state().app_context;
+        let mut now = ctx.frame_time;
+        spring_frame(ctx, now, 0.);
+        spring_frame(ctx, now, 100.);
+        for _ in 0..5 {
+            now += Duration::from_millis(16);
+            spring_frame(ctx, now, 100.);
+        }
+        let (value, speed) = spring_frame(ctx, now, 100.);
+        assert!(speed > 100.);
+        let (retargeted, retargeted_speed) = spring_frame(ctx, now, 200.);
+        assert_eq!(retargeted, value);
+        assert!(
+            (retargeted_speed - speed).abs() < speed * 0.01,
+            "{speed} became {retargeted_speed}"
+        );
+        for _ in 0..200 {
+            now += Duration::from_millis(16);
+            spring_frame(ctx, now, 200.);
+        }
+        let (value, speed) = spring_frame(ctx, now, 200.);
+        assert!((value - 200.).abs() < 0.001);
+        assert_eq!(speed, 0.);
+    }
+}
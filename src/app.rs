@@ -1,10 +1,12 @@
 use crate::draw_layout::draw_layout;
-use crate::gestures::{ClickLocation, Interaction, ScrollDelta};
+use crate::input::{InputState, Placement};
+use crate::shortcut::{Accelerator, Shortcut};
 
 use crate::text::TextLayout;
+use crate::timers::{AppTimer, Clock};
 use crate::view::DrawableType;
-use crate::{ClickState, DragState, Editor, GestureHandler, Point, area_contains};
-use crate::{GestureState, RUBIK_FONT, area_contains_padded, event};
+use crate::{Editor, GestureHandler, Insets, KeyEvent, Point};
+use crate::{RUBIK_FONT, event};
 use backer::{Area, Layout};
 use parley::fontique::Blob;
 use parley::fontique::FontInfoOverride;
@@ -12,20 +14,22 @@ use parley::{
     Alignment, FontContext, FontWeight, LayoutContext, LineHeight, OverflowWrap, PlainEditor,
     StyleProperty,
 };
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc::Sender;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
 use vello_svg::vello::kurbo::{Affine, BezPath};
-use vello_svg::vello::peniko::{Brush, Color, Fill, Mix};
+use vello_svg::vello::peniko::{BlendMode, Brush, Color, Fill, Mix};
 use vello_svg::vello::util::{RenderContext, RenderSurface};
 use vello_svg::vello::{Renderer, RendererOptions, Scene};
-use winit::event::{Modifiers, MouseScrollDelta};
-use winit::event_loop::ActiveEventLoop;
-use winit::window::{Fullscreen, WindowId};
+use winit::event::{Modifiers, MouseScrollDelta, TouchPhase};
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
+use winit::window::{CursorIcon, Fullscreen, WindowId};
 use winit::{
     application::ApplicationHandler, event_loop::EventLoop, window::Window as WinitWindow,
 };
@@ -34,9 +38,10 @@ use winit::{dpi::LogicalSize, event::MouseButton};
 #[cfg(target_os = "macos")]
 use winit::platform::macos::WindowAttributesExtMacOS;
 
-type FontEntry = (Arc<Vec<u8>>, Option<String>);
+pub(crate) type FontEntry = (Arc<Vec<u8>>, Option<String>);
 
-type ViewFn<State> = for<'a> fn(&'a State, &mut AppState) -> Layout<'a, View<State>, AppCtx>;
+pub(crate) type ViewFn<State> =
+    for<'a> fn(&'a State, &mut AppState) -> Layout<'a, View<State>, AppCtx>;
 
 pub struct Window<State> {
     name: &'static str,
@@ -98,10 +103,12 @@ pub struct AppBuilder<State> {
     state: State,
     window_registry: HashMap<&'static str, Window<State>>,
     initial_windows: Vec<&'static str>,
-    on_frame: fn(&mut State, &mut AppState) -> (),
+    on_frame: fn(&mut State, &mut AppState, FrameTime) -> (),
     on_start: fn(&mut State, &mut AppState) -> (),
     on_exit: fn(&mut State, &mut AppState) -> (),
     custom_fonts: Vec<FontEntry>,
+    shortcuts: Vec<Shortcut>,
+    hit_slop: Insets,
 }
 
 impl<State: 'static> AppBuilder<State> {
@@ -117,10 +124,12 @@ impl<State: 'static> AppBuilder<State> {
             state,
             window_registry: registry,
             initial_windows,
-            on_frame: |_, _| {},
+            on_frame: |_, _, _| {},
             on_start: |_, _| {},
             on_exit: |_, _| {},
             custom_fonts: Vec::new(),
+            shortcuts: Vec::new(),
+            hit_slop: Insets::ZERO,
         }
     }
 
@@ -139,7 +148,8 @@ impl<State: 'static> AppBuilder<State> {
         self
     }
 
-    pub fn on_frame(mut self, on_frame: fn(&mut State, &mut AppState) -> ()) -> Self {
+    /// Runs after each frame is laid out, with the frame's timing.
+    pub fn on_frame(mut self, on_frame: fn(&mut State, &mut AppState, FrameTime) -> ()) -> Self {
         self.on_frame = on_frame;
         self
     }
@@ -154,6 +164,24 @@ impl<State: 'static> AppBuilder<State> {
         self
     }
 
+    /// Registers an application-wide shortcut, see `AppState::register_shortcut`.
+    ///
+    /// Panics if `accelerator` can't be parsed.
+    pub fn shortcut(mut self, accelerator: &str, f: fn(&mut State, &mut AppState) -> ()) -> Self {
+        let accelerator: Accelerator = accelerator
+            .parse()
+            .unwrap_or_else(|err| panic!("Invalid shortcut {err}"));
+        self.shortcuts.retain(|s| s.accelerator != accelerator);
+        self.shortcuts.push(Shortcut::new(accelerator, f));
+        self
+    }
+
+    /// The default hit slop of views, see `AppState::set_hit_slop`.
+    pub fn hit_slop(mut self, insets: impl Into<Insets>) -> Self {
+        self.hit_slop = insets.into();
+        self
+    }
+
     pub fn start(self) {
         let event_loop: EventLoop<AppEvent> = EventLoop::with_user_event()
             .build()
@@ -172,6 +200,8 @@ impl<State: 'static> AppBuilder<State> {
                 self.on_start,
                 self.on_exit,
                 self.custom_fonts,
+                self.shortcuts,
+                self.hit_slop,
             );
         }
     }
@@ -185,7 +215,7 @@ pub struct App<'s, State> {
     pub(crate) initial_windows: Vec<&'static str>,
     pub(crate) app_state: AppState,
     pub state: State,
-    pub(crate) on_frame: fn(&mut State, &mut AppState) -> (),
+    pub(crate) on_frame: fn(&mut State, &mut AppState, FrameTime) -> (),
     pub(crate) on_start: fn(&mut State, &mut AppState) -> (),
     pub(crate) on_exit: fn(&mut State, &mut AppState) -> (),
     pub(crate) started: bool,
@@ -199,11 +229,25 @@ pub(crate) struct WindowState<'surface, State> {
     pub(crate) scene: Scene,
     pub(crate) name: &'static str,
     pub(crate) view: ViewFn<State>,
-    pub(crate) gesture_handlers: Vec<(u64, Area, GestureHandler<State, AppState>)>,
-    pub(crate) cursor_position: Option<Point>,
-    pub(crate) gesture_state: GestureState,
+    pub(crate) input: InputState<State>,
     pub(crate) last_window_size: Option<winit::dpi::PhysicalSize<u32>>,
     pub(crate) fullscreen_requested: bool,
+    /// Files reported one at a time by the OS, delivered together once the batch ends.
+    pub(crate) hovered_files: Vec<PathBuf>,
+    pub(crate) dropped_files: Vec<PathBuf>,
+    /// The cursor icon last set on the window.
+    pub(crate) cursor: CursorIcon,
+    /// Animations read by the window's last frame.
+    pub(crate) animations: HashSet<u64>,
+}
+
+impl<State> WindowState<'_, State> {
+    fn sync_cursor(&mut self) {
+        if self.cursor != self.input.cursor {
+            self.cursor = self.input.cursor;
+            self.window.set_cursor(self.cursor);
+        }
+    }
 }
 
 pub(crate) type LayoutCache = HashMap<u64, Vec<(String, f32, parley::Layout<Brush>)>>;
@@ -214,7 +258,36 @@ pub struct AppCtx {
     pub(crate) layout_cx: LayoutContext<Brush>,
     pub(crate) scale_factor: f64,
     pub(crate) editor: Option<EditState>,
-    pub(crate) editor_areas: HashMap<u64, Area>,
+    /// Where each text field's editor is drawn, with the transform it was drawn in.
+    pub(crate) editor_areas: HashMap<u64, (Area, Placement)>,
+    /// When the frame being laid out started.
+    pub(crate) frame_time: Instant,
+    /// Time since the previous frame started.
+    pub(crate) frame_delta: Duration,
+    /// Set by views that are still moving and need another frame.
+    pub(crate) frame_requested: bool,
+    /// The state of `AppCtx::animate` values, keyed by id.
+    pub(crate) animations: HashMap<u64, Box<dyn Any>>,
+    /// Animations read during the frame being laid out.
+    pub(crate) animations_used: HashSet<u64>,
+}
+
+impl AppCtx {
+    pub(crate) fn begin_frame(&mut self, now: Instant) {
+        self.frame_delta = now.saturating_duration_since(self.frame_time);
+        self.frame_time = now;
+        self.frame_requested = false;
+        self.animations_used.clear();
+    }
+
+    /// Drops the animations a window's previous frame read and this one didn't, then
+    /// records this frame's in `used` for the next.
+    pub(crate) fn prune_animations(&mut self, used: &mut HashSet<u64>) {
+        for id in used.difference(&self.animations_used) {
+            self.animations.remove(id);
+        }
+        *used = std::mem::take(&mut self.animations_used);
+    }
 }
 
 pub struct AppState {
@@ -227,8 +300,25 @@ pub struct AppState {
     pub(crate) image_scenes: HashMap<u64, (Scene, f32, f32)>,
     pub(crate) modifiers: Option<Modifiers>,
     pub(crate) redraw: Sender<()>,
-    pub(crate) event_proxy: winit::event_loop::EventLoopProxy<AppEvent>,
+    pub(crate) event_proxy: EventSender,
     pub(crate) cursor_position: Option<Point>,
+    pub(crate) focused: Option<u64>,
+    pub(crate) shortcuts: Vec<Shortcut>,
+    pub(crate) propagation_stopped: bool,
+    pub(crate) hit_slop: Insets,
+    pub(crate) clock: Clock,
+    /// Timeouts and intervals, in the order they were set.
+    pub(crate) timers: Vec<AppTimer>,
+    pub(crate) next_timer_id: u64,
+}
+
+/// The timing of a frame, passed to `on_frame`.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameTime {
+    /// When the frame started.
+    pub timestamp: Instant,
+    /// Time since the previous frame, or since the app started for the first one.
+    pub delta: Duration,
 }
 
 pub enum View<State> {
@@ -241,6 +331,18 @@ pub enum View<State> {
         path: BezPath,
     },
     PopClip,
+    /// Composites everything drawn until the matching `PopLayer` with `blend` and `alpha`.
+    PushLayer {
+        path: BezPath,
+        alpha: f32,
+        blend: BlendMode,
+    },
+    PopLayer,
+    /// Transforms everything drawn until the matching `PopTransform`, in logical coordinates.
+    PushTransform {
+        transform: Affine,
+    },
+    PopTransform,
     EditorArea(u64, Area),
     Empty,
 }
@@ -266,6 +368,66 @@ impl Clone for EditState {
 }
 
 impl AppState {
+    pub(crate) fn new(
+        runtime: Runtime,
+        event_proxy: EventSender,
+        redraw: Sender<()>,
+        custom_fonts: Vec<FontEntry>,
+    ) -> Self {
+        let mut font_cx = FontContext::new();
+
+        font_cx
+            .collection
+            .register_fonts(Blob::new(Arc::new(RUBIK_FONT)), None);
+
+        for (font_bytes, family_opt) in custom_fonts.into_iter() {
+            font_cx.collection.register_fonts(
+                Blob::new(font_bytes),
+                Some(FontInfoOverride {
+                    family_name: family_opt.as_deref(),
+                    ..Default::default()
+                }),
+            );
+        }
+
+        let layout_cache = HashMap::new();
+        let layout_cx = LayoutContext::new();
+        let font_cx_inner = FontContext::new();
+
+        AppState {
+            runtime,
+            cancellation_token: CancellationToken::new(),
+            task_tracker: TaskTracker::new(),
+            app_context: AppCtx {
+                text_layout: TextLayout::new(layout_cache, font_cx_inner, layout_cx),
+                font_cx: FontContext::new(),
+                layout_cx: LayoutContext::new(),
+                scale_factor: 1.,
+                editor: None,
+                editor_areas: HashMap::new(),
+                frame_time: Instant::now(),
+                frame_delta: Duration::ZERO,
+                frame_requested: false,
+                animations: HashMap::new(),
+                animations_used: HashSet::new(),
+            },
+            layout_cache: HashMap::new(),
+            image_scenes: HashMap::new(),
+            svg_scenes: HashMap::new(),
+            modifiers: None,
+            redraw,
+            event_proxy,
+            cursor_position: None,
+            focused: None,
+            shortcuts: Vec::new(),
+            propagation_stopped: false,
+            hit_slop: Insets::ZERO,
+            clock: Clock::default(),
+            timers: Vec::new(),
+            next_timer_id: 0,
+        }
+    }
+
     pub fn ctx(&mut self) -> &mut AppCtx {
         &mut self.app_context
     }
@@ -276,6 +438,48 @@ impl AppState {
         }
     }
 
+    /// Gives keyboard focus to the view with `id`.
+    pub fn focus(&mut self, id: u64) {
+        self.focused = Some(id);
+    }
+
+    pub fn blur(&mut self) {
+        self.focused = None;
+    }
+
+    pub fn focused(&self) -> Option<u64> {
+        self.focused
+    }
+
+    /// Sets the hit slop of views that don't set their own with `Drawable::hit_slop`.
+    pub fn set_hit_slop(&mut self, insets: impl Into<Insets>) {
+        self.hit_slop = insets.into();
+    }
+
+    /// Keeps the pointer event being handled from reaching `pass_through` views further down.
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    /// Calls `f` when `accelerator` is pressed, regardless of focus, unless a text editor
+    /// handles the key. Replaces any shortcut already registered for `accelerator`.
+    pub fn register_shortcut<State: 'static>(
+        &mut self,
+        accelerator: &str,
+        f: impl Fn(&mut State, &mut AppState) + 'static,
+    ) -> Result<(), String> {
+        let accelerator: Accelerator = accelerator.parse()?;
+        self.shortcuts.retain(|s| s.accelerator != accelerator);
+        self.shortcuts.push(Shortcut::new(accelerator, f));
+        Ok(())
+    }
+
+    pub fn unregister_shortcut(&mut self, accelerator: &str) {
+        if let Ok(accelerator) = accelerator.parse::<Accelerator>() {
+            self.shortcuts.retain(|s| s.accelerator != accelerator);
+        }
+    }
+
     pub fn open_window(&mut self, name: &'static str) {
         let _ = self.event_proxy.send_event(AppEvent::OpenWindow(name));
     }
@@ -302,7 +506,7 @@ impl AppState {
         if self.app_context.editor.is_some() {
             return;
         }
-        let Some(area) = self.app_context.editor_areas.get(&id) else {
+        let Some((area, placement)) = self.app_context.editor_areas.get(&id).cloned() else {
             return;
         };
         let mut editor = PlainEditor::new(font_size);
@@ -324,8 +528,6 @@ impl AppState {
         }
         let mut editor = Editor {
             editor,
-            last_click_time: Default::default(),
-            click_count: Default::default(),
             pointer_down: Default::default(),
             cursor_pos: Default::default(),
             cursor_visible: Default::default(),
@@ -336,7 +538,7 @@ impl AppState {
 
         if let Some(pos) = self.cursor_position {
             editor.mouse_moved(
-                Point::new(pos.x - area.x as f64, pos.y - area.y as f64),
+                placement.local(&area, pos),
                 &mut self.app_context.layout_cx,
                 &mut self.app_context.font_cx,
             );
@@ -383,7 +585,7 @@ impl AppState {
 
 #[derive(Clone)]
 pub struct Callback<T> {
-    event_proxy: winit::event_loop::EventLoopProxy<AppEvent>,
+    event_proxy: EventSender,
     handler: Arc<dyn Fn(T) -> Box<dyn FnOnce(&mut dyn std::any::Any) + Send> + Send + Sync>,
 }
 
@@ -430,16 +632,6 @@ impl<State: 'static> App<'_, State> {
         }
     }
 
-    fn gesture_handlers(
-        &self,
-        window_id: winit::window::WindowId,
-    ) -> Vec<(u64, Area, GestureHandler<State, AppState>)> {
-        self.windows
-            .get(&window_id)
-            .map(|ws| ws.gesture_handlers.clone())
-            .unwrap_or_default()
-    }
-
     fn remove_window(&mut self, id: WindowId) {
         self.windows.remove(&id);
         if !self.windows.is_empty() {
@@ -535,11 +727,13 @@ impl<State: 'static> App<'_, State> {
                 scene: Scene::new(),
                 name,
                 view: config.view,
-                gesture_handlers: Vec::new(),
-                cursor_position: None,
-                gesture_state: GestureState::None,
+                input: InputState::default(),
                 last_window_size: None,
                 fullscreen_requested: false,
+                hovered_files: Vec::new(),
+                dropped_files: Vec::new(),
+                cursor: CursorIcon::Default,
+                animations: HashSet::new(),
             },
         );
     }
@@ -551,30 +745,16 @@ impl<State: 'static> App<'_, State> {
         #[cfg(target_arch = "wasm32")] render_state: RenderState,
         window_registry: HashMap<&'static str, Window<State>>,
         initial_windows: Vec<&'static str>,
-        on_frame: fn(&mut State, &mut AppState) -> (),
+        on_frame: fn(&mut State, &mut AppState, FrameTime) -> (),
         on_start: fn(&mut State, &mut AppState) -> (),
         on_exit: fn(&mut State, &mut AppState) -> (),
         custom_fonts: Vec<FontEntry>,
+        shortcuts: Vec<Shortcut>,
+        hit_slop: Insets,
     ) {
         #[allow(unused_mut)]
         let mut renderers: Vec<Option<Renderer>> = vec![];
 
-        let mut font_cx = FontContext::new();
-
-        font_cx
-            .collection
-            .register_fonts(Blob::new(Arc::new(RUBIK_FONT)), None);
-
-        for (font_bytes, family_opt) in custom_fonts.into_iter() {
-            font_cx.collection.register_fonts(
-                Blob::new(font_bytes),
-                Some(FontInfoOverride {
-                    family_name: family_opt.as_deref(),
-                    ..Default::default()
-                }),
-            );
-        }
-
         let runtime = Runtime::new().expect("Failed to create runtime");
 
         let redraw_proxy = event_loop.create_proxy();
@@ -590,10 +770,6 @@ impl<State: 'static> App<'_, State> {
             }
         });
 
-        let layout_cache = HashMap::new();
-        let layout_cx = LayoutContext::new();
-        let font_cx_inner = FontContext::new();
-
         let mut app = Self {
             context: render_cx,
             renderers,
@@ -601,31 +777,19 @@ impl<State: 'static> App<'_, State> {
             window_registry,
             initial_windows,
             state,
-            app_state: AppState {
+            app_state: AppState::new(
                 runtime,
-                cancellation_token: CancellationToken::new(),
-                task_tracker: TaskTracker::new(),
-                app_context: AppCtx {
-                    text_layout: TextLayout::new(layout_cache, font_cx_inner, layout_cx),
-                    font_cx: FontContext::new(),
-                    layout_cx: LayoutContext::new(),
-                    scale_factor: 1.,
-                    editor: None,
-                    editor_areas: HashMap::new(),
-                },
-                layout_cache: HashMap::new(),
-                image_scenes: HashMap::new(),
-                svg_scenes: HashMap::new(),
-                modifiers: None,
-                redraw: redraw_sender,
-                event_proxy,
-                cursor_position: None,
-            },
+                EventSender::Proxy(event_proxy),
+                redraw_sender,
+                custom_fonts,
+            ),
             on_frame,
             on_start,
             on_exit,
             started: false,
         };
+        app.app_state.shortcuts = shortcuts;
+        app.app_state.hit_slop = hit_slop;
 
         event_loop.run_app(&mut app).expect("run to completion");
         (app.on_exit)(&mut app.state, &mut app.app_state);
@@ -651,7 +815,9 @@ impl<State: 'static> App<'_, State> {
             return;
         };
 
-        ws.gesture_handlers.clear();
+        ws.input.gesture_handlers.clear();
+        let now = self.app_state.clock.now();
+        self.app_state.app_context.begin_frame(now);
         let size = ws.window.inner_size();
         ws.last_window_size = Some(size);
         self.app_state.app_context.scale_factor = ws.window.scale_factor();
@@ -662,7 +828,7 @@ impl<State: 'static> App<'_, State> {
         }
 
         let view = ws.view;
-        let draw_items = {
+        let mut draw_items = {
             let mut layout = view(&self.state, &mut self.app_state);
             layout.draw(
                 Area {
@@ -676,22 +842,67 @@ impl<State: 'static> App<'_, State> {
         };
 
         let ws = self.windows.get_mut(&window_id).unwrap();
+        let scale = Affine::scale(self.app_state.app_context.scale_factor);
+        let mut clip_stack: Vec<BezPath> = Vec::new();
+        let mut placement = Placement::default();
+        // Clip and blend layers pushed and not yet popped
+        let mut open_layers = 0;
+        // Transformed content is drawn into a scene of its own, which is appended to the
+        // enclosing one with the transform when it ends. Each keeps the layer depth it began at
+        let mut scenes: Vec<(Scene, Affine, usize)> = Vec::new();
+        draw_items.extend(
+            ws.input
+                .drag_preview(&self.state, &mut self.app_state.app_context),
+        );
+        self.app_state
+            .app_context
+            .prune_animations(&mut ws.animations);
         for item in draw_items {
+            let layer_depth = scenes.last().map_or(0, |(.., depth)| *depth);
+            let scene = scenes.last_mut().map_or(&mut ws.scene, |(scene, ..)| scene);
             match item {
                 View::PushClip { path } => {
-                    ws.scene.push_layer(
-                        Fill::NonZero,
-                        Mix::Normal,
-                        1.,
-                        Affine::scale(self.app_state.app_context.scale_factor),
-                        &path,
-                    );
+                    scene.push_layer(Fill::NonZero, Mix::Normal, 1., scale, &path);
+                    open_layers += 1;
+                    clip_stack.push(placement.transform * path);
+                    placement.clips = clip_stack.as_slice().into();
                 }
                 View::PopClip => {
-                    ws.scene.pop_layer();
+                    scene.pop_layer();
+                    open_layers -= 1;
+                    clip_stack.pop();
+                    placement.clips = clip_stack.as_slice().into();
+                }
+                View::PushLayer { path, alpha, blend } => {
+                    scene.push_layer(Fill::NonZero, blend, alpha, scale, &path);
+                    open_layers += 1;
+                }
+                View::PopLayer => {
+                    scene.pop_layer();
+                    open_layers -= 1;
+                }
+                View::PushTransform { transform } => {
+                    scenes.push((Scene::new(), transform, open_layers));
+                    placement.transform *= transform;
+                }
+                View::PopTransform => {
+                    if let Some((layer, transform, _)) = scenes.pop() {
+                        scenes
+                            .last_mut()
+                            .map_or(&mut ws.scene, |(scene, ..)| scene)
+                            .append(&layer, Some(scale * transform * scale.inverse()));
+                    }
+                    placement.transform = scenes
+                        .iter()
+                        .fold(Affine::IDENTITY, |total, (_, transform, _)| {
+                            total * *transform
+                        });
                 }
                 View::EditorArea(id, area) => {
-                    self.app_state.app_context.editor_areas.insert(id, area);
+                    self.app_state
+                        .app_context
+                        .editor_areas
+                        .insert(id, (area, placement.clone()));
                 }
                 View::Draw {
                     mut view,
@@ -701,40 +912,46 @@ impl<State: 'static> App<'_, State> {
                     let id = view.id();
                     let draw_area = area;
 
-                    ws.gesture_handlers.extend(
+                    ws.input.gesture_handlers.extend(
                         gesture_handlers
                             .into_iter()
-                            .map(|handler| (id, draw_area, handler)),
+                            .map(|handler| (id, draw_area, handler, placement.clone())),
                     );
 
                     match &mut *view {
                         DrawableType::Text(v) => {
-                            v.draw(draw_area, area, &mut ws.scene, &mut self.app_state)
+                            v.draw(draw_area, area, scene, &mut self.app_state)
                         }
                         DrawableType::Layout(boxed) => {
                             let (layout, transform) = boxed.as_mut();
-                            draw_layout(None, *transform, layout, &mut ws.scene)
+                            draw_layout(None, *transform, layout, scene)
                         }
                         DrawableType::Path(v) => v.draw(
-                            &mut ws.scene,
+                            scene,
                             draw_area,
                             self.app_state.app_context.scale_factor,
+                            open_layers - layer_depth,
                         ),
-                        DrawableType::Svg(v) => {
-                            v.draw(draw_area, &mut ws.scene, &mut self.app_state)
-                        }
-                        DrawableType::Image(v) => {
-                            v.draw(draw_area, &mut ws.scene, &mut self.app_state)
-                        }
+                        DrawableType::Svg(v) => v.draw(draw_area, scene, &mut self.app_state),
+                        DrawableType::Image(v) => v.draw(draw_area, scene, &mut self.app_state),
                     }
                 }
                 View::Empty => (),
             }
         }
 
-        (self.on_frame)(&mut self.state, &mut self.app_state);
+        let frame_time = FrameTime {
+            timestamp: self.app_state.app_context.frame_time,
+            delta: self.app_state.app_context.frame_delta,
+        };
+        (self.on_frame)(&mut self.state, &mut self.app_state, frame_time);
 
         let ws = self.windows.get_mut(&window_id).unwrap();
+        if ws.input.sync_focus(&mut self.state, &mut self.app_state)
+            || std::mem::take(&mut self.app_state.app_context.frame_requested)
+        {
+            ws.window.request_redraw();
+        }
         let size = ws.window.inner_size();
         let width = size.width;
         let height = size.height;
@@ -807,6 +1024,23 @@ impl std::fmt::Debug for AppEvent {
     }
 }
 
+/// Delivers `AppEvent`s either to a running winit event loop or, when running
+/// headless, to a queue drained by the owner of the `AppState`.
+#[derive(Clone)]
+pub(crate) enum EventSender {
+    Proxy(winit::event_loop::EventLoopProxy<AppEvent>),
+    Queue(std::sync::mpsc::Sender<AppEvent>),
+}
+
+impl EventSender {
+    pub(crate) fn send_event(&self, event: AppEvent) -> Result<(), ()> {
+        match self {
+            EventSender::Proxy(proxy) => proxy.send_event(event).map_err(|_| ()),
+            EventSender::Queue(queue) => queue.send(event).map_err(|_| ()),
+        }
+    }
+}
+
 impl<State: 'static> ApplicationHandler<AppEvent> for App<'_, State> {
     fn user_event(&mut self, event_loop: &ActiveEventLoop, event: AppEvent) {
         match event {
@@ -826,6 +1060,41 @@ impl<State: 'static> ApplicationHandler<AppEvent> for App<'_, State> {
         }
     }
 
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let ran_timers = self.app_state.run_timers(&mut self.state);
+        // Frames requested outside of a frame, e.g. by a timer, redraw every window
+        let frame_requested =
+            std::mem::take(&mut self.app_state.app_context.frame_requested) || ran_timers;
+        let mut deadline = self.app_state.next_timer_deadline();
+        for ws in self.windows.values_mut() {
+            let mut needs_redraw = frame_requested;
+            if !ws.hovered_files.is_empty() {
+                needs_redraw |= ws.input.files_hovered(
+                    &mut self.state,
+                    &mut self.app_state,
+                    std::mem::take(&mut ws.hovered_files),
+                );
+            }
+            if !ws.dropped_files.is_empty() {
+                needs_redraw |= ws.input.files_dropped(
+                    &mut self.state,
+                    &mut self.app_state,
+                    std::mem::take(&mut ws.dropped_files),
+                );
+            }
+            needs_redraw |= ws.input.fire_timers(&mut self.state, &mut self.app_state);
+            if needs_redraw {
+                ws.window.request_redraw();
+            }
+            deadline = deadline.into_iter().chain(ws.input.next_deadline()).min();
+        }
+        // Wake up again when the next timer, long press or hover intent is due
+        event_loop.set_control_flow(match deadline {
+            Some(deadline) => ControlFlow::WaitUntil(deadline),
+            None => ControlFlow::Wait,
+        });
+    }
+
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         for window in self.initial_windows.clone() {
             self.create_window(event_loop, window);
@@ -841,67 +1110,116 @@ impl<State: 'static> ApplicationHandler<AppEvent> for App<'_, State> {
         if let Some(event) = crate::event::WindowEvent::from_winit_window_event(event) {
             match event {
                 event::WindowEvent::Moved(_) => {}
-                event::WindowEvent::KeyPressed(key) => {
-                    let Some(key) = crate::Key::from(key) else {
+                event::WindowEvent::KeyPressed(event) | event::WindowEvent::KeyReleased(event) => {
+                    let Some(key) = crate::Key::from(event.logical_key) else {
                         return;
                     };
-                    let mut needs_redraw = false;
-                    for (_id, _area, handler) in self.gesture_handlers(window_id) {
-                        if let Some(ref interaction_handler) = handler.interaction_handler
-                            && handler.interaction_type.key
-                        {
-                            needs_redraw = true;
-                            interaction_handler(
-                                &mut self.state,
-                                &mut self.app_state,
-                                Interaction::Key(key.clone()),
-                            );
-                        }
-                    }
-                    if needs_redraw {
-                        self.request_redraw_window(window_id);
+                    let event = KeyEvent {
+                        key,
+                        physical_key: event.physical_key,
+                        state: event.state,
+                        is_repeat: event.repeat,
+                        modifiers: self
+                            .app_state
+                            .modifiers
+                            .map(|modifiers| modifiers.state())
+                            .unwrap_or_default(),
+                    };
+                    if let Some(ws) = self.windows.get_mut(&window_id)
+                        && ws
+                            .input
+                            .key_input(&mut self.state, &mut self.app_state, event)
+                    {
+                        ws.window.request_redraw();
                     }
                 }
-                event::WindowEvent::KeyReleased(_) => {}
                 event::WindowEvent::MouseMoved(pos) => self.mouse_moved(window_id, pos),
-                event::WindowEvent::MousePressed(MouseButton::Left) => {
-                    self.mouse_pressed(window_id)
-                }
-                event::WindowEvent::MouseReleased(MouseButton::Left) => {
-                    self.mouse_released(window_id)
-                }
-                event::WindowEvent::MousePressed(_) => {}
-                event::WindowEvent::MouseReleased(_) => {}
+                event::WindowEvent::MousePressed(button) => self.mouse_pressed(window_id, button),
+                event::WindowEvent::MouseReleased(button) => self.mouse_released(window_id, button),
                 event::WindowEvent::MouseEntered => {}
                 event::WindowEvent::MouseExited => {
                     if let Some(ws) = self.windows.get_mut(&window_id) {
-                        ws.cursor_position = None;
+                        if ws.input.mouse_exited(&mut self.state, &mut self.app_state) {
+                            ws.window.request_redraw();
+                        }
+                        ws.sync_cursor();
+                    }
+                }
+                event::WindowEvent::MouseWheel(delta, phase) => {
+                    self.scrolled(window_id, delta, phase);
+                }
+                event::WindowEvent::Resized(_) => {}
+                event::WindowEvent::HoveredFile(path) => {
+                    if let Some(ws) = self.windows.get_mut(&window_id) {
+                        ws.hovered_files.push(path);
+                    }
+                }
+                event::WindowEvent::DroppedFile(path) => {
+                    if let Some(ws) = self.windows.get_mut(&window_id) {
+                        ws.dropped_files.push(path);
                     }
-                    let mut needs_redraw = false;
-                    for (_, _, gh) in self.gesture_handlers(window_id) {
-                        if gh.interaction_type.hover
-                            && let Some(ref on_hover) = gh.interaction_handler
+                }
+                event::WindowEvent::HoveredFileCancelled => {
+                    if let Some(ws) = self.windows.get_mut(&window_id) {
+                        ws.hovered_files.clear();
+                        if ws
+                            .input
+                            .file_hover_cancelled(&mut self.state, &mut self.app_state)
                         {
-                            needs_redraw = true;
-                            on_hover(
-                                &mut self.state,
-                                &mut self.app_state,
-                                Interaction::Hover(false),
-                            );
+                            ws.window.request_redraw();
                         }
                     }
-                    if needs_redraw {
-                        self.request_redraw_window(window_id);
+                }
+                event::WindowEvent::Touch(touch) => {
+                    let scale_factor = self.app_state.app_context.scale_factor;
+                    let touch = event::TouchEvent {
+                        position: Point::new(
+                            touch.position.x / scale_factor,
+                            touch.position.y / scale_factor,
+                        ),
+                        ..touch
+                    };
+                    if let Some(ws) = self.windows.get_mut(&window_id)
+                        && ws.input.touch(&mut self.state, &mut self.app_state, touch)
+                    {
+                        ws.window.request_redraw();
+                    }
+                }
+                event::WindowEvent::PinchGesture(delta, phase) => {
+                    if let Some(ws) = self.windows.get_mut(&window_id)
+                        && ws.input.trackpad_pinch(
+                            &mut self.state,
+                            &mut self.app_state,
+                            delta,
+                            phase,
+                        )
+                    {
+                        ws.window.request_redraw();
                     }
                 }
-                event::WindowEvent::MouseWheel(delta, _phase) => {
-                    self.scrolled(window_id, delta);
+                event::WindowEvent::RotationGesture(delta, phase) => {
+                    if let Some(ws) = self.windows.get_mut(&window_id)
+                        && ws.input.trackpad_rotate(
+                            &mut self.state,
+                            &mut self.app_state,
+                            delta,
+                            phase,
+                        )
+                    {
+                        ws.window.request_redraw();
+                    }
+                }
+                event::WindowEvent::PanGesture(delta, phase) => {
+                    let scale_factor = self.app_state.app_context.scale_factor;
+                    let delta = Point::new(delta.x / scale_factor, delta.y / scale_factor);
+                    if let Some(ws) = self.windows.get_mut(&window_id)
+                        && ws
+                            .input
+                            .trackpad_pan(&mut self.state, &mut self.app_state, delta, phase)
+                    {
+                        ws.window.request_redraw();
+                    }
                 }
-                event::WindowEvent::Resized(_) => {}
-                event::WindowEvent::HoveredFile(_) => {}
-                event::WindowEvent::DroppedFile(_) => {}
-                event::WindowEvent::HoveredFileCancelled => {}
-                event::WindowEvent::Touch(_) => {}
                 event::WindowEvent::TouchPressure(_) => {}
                 event::WindowEvent::Focused => {
                     self.request_redraw_window(window_id);
@@ -932,354 +1250,79 @@ impl<State: 'static> App<'_, State> {
             pos.x / self.app_state.app_context.scale_factor,
             pos.y / self.app_state.app_context.scale_factor,
         );
-        let mut needs_redraw = false;
         if let Some(ws) = self.windows.get_mut(&window_id) {
-            ws.cursor_position = Some(pos);
-        }
-        self.app_state.cursor_position = Some(pos);
-        if let Some(EditState { id, editor, .. }) = self.app_state.app_context.editor.as_mut()
-            && let Some(area) = self.app_state.app_context.editor_areas.get(id).copied()
-        {
-            needs_redraw = true;
-            editor.mouse_moved(
-                Point::new(pos.x - area.x as f64, pos.y - area.y as f64),
-                &mut self.app_state.app_context.layout_cx,
-                &mut self.app_state.app_context.font_cx,
-            );
-        }
-        self.gesture_handlers(window_id)
-            .iter()
-            .for_each(|(_, area, gh)| {
-                if gh.interaction_type.hover
-                    && let Some(ref on_hover) = gh.interaction_handler
-                {
-                    needs_redraw = true;
-                    (on_hover)(
-                        &mut self.state,
-                        &mut self.app_state,
-                        Interaction::Hover(area_contains(area, pos)),
-                    );
-                }
-            });
-        let gesture_state = self
-            .windows
-            .get(&window_id)
-            .map(|ws| ws.gesture_state)
-            .unwrap_or(GestureState::None);
-        if let GestureState::Dragging {
-            start,
-            last_position,
-            capturer,
-        } = gesture_state
-        {
-            let distance = start.distance(pos);
-            let delta = Point {
-                x: pos.x - last_position.x,
-                y: pos.y - last_position.y,
-            };
-            self.gesture_handlers(window_id)
-                .iter()
-                .filter(|(id, _, gh)| *id == capturer && gh.interaction_type.drag)
-                .for_each(|(_, area, gh)| {
-                    needs_redraw = true;
-                    if let Some(handler) = &gh.interaction_handler {
-                        (handler)(
-                            &mut self.state,
-                            &mut self.app_state,
-                            Interaction::Drag(DragState::Updated {
-                                start: Point {
-                                    x: start.x - area.x as f64,
-                                    y: start.y - area.y as f64,
-                                },
-                                current: Point {
-                                    x: pos.x - area.x as f64,
-                                    y: pos.y - area.y as f64,
-                                },
-                                start_global: start,
-                                current_global: pos,
-                                delta,
-                                distance: distance as f32,
-                            }),
-                        );
-                    }
-                });
-            if let Some(ws) = self.windows.get_mut(&window_id) {
-                ws.gesture_state = GestureState::Dragging {
-                    start,
-                    last_position: pos,
-                    capturer,
-                };
-            }
-        }
-        if needs_redraw {
-            self.request_redraw_window(window_id);
-        }
-    }
-    pub(crate) fn mouse_pressed(&mut self, window_id: winit::window::WindowId) {
-        let mut needs_redraw = false;
-        let cursor_position = self
-            .windows
-            .get(&window_id)
-            .and_then(|ws| ws.cursor_position);
-        if let Some(point) = cursor_position {
-            for (_, area, handler) in
-                self.gesture_handlers(window_id)
-                    .iter()
-                    .rev()
-                    .filter(|(_, area, handler)| {
-                        handler.interaction_type.click_outside
-                            && !area_contains_padded(area, point, 10.)
-                    })
+            if ws
+                .input
+                .mouse_moved(&mut self.state, &mut self.app_state, pos)
             {
-                if handler.interaction_type.click_outside
-                    && let Some(ref on_click_outside) = handler.interaction_handler
-                {
-                    on_click_outside(
-                        &mut self.state,
-                        &mut self.app_state,
-                        Interaction::ClickOutside(
-                            ClickState::Started,
-                            ClickLocation::new(point, *area),
-                        ),
-                    );
-                }
-            }
-            let handlers = self.gesture_handlers(window_id);
-            if let Some((capturer, area, handler)) = handlers
-                .iter()
-                .rev()
-                .find(|(_, area, handler)| {
-                    area_contains(area, point)
-                        && (handler.interaction_type.click || handler.interaction_type.drag)
-                })
-                .or(handlers.iter().rev().find(|(_, area, handler)| {
-                    area_contains(
-                        &Area {
-                            x: area.x - 10.,
-                            y: area.y - 10.,
-                            width: area.width + 20.,
-                            height: area.height + 20.,
-                        },
-                        point,
-                    ) && (handler.interaction_type.click || handler.interaction_type.drag)
-                }))
-            {
-                needs_redraw = true;
-                if handler.interaction_type.click
-                    && let Some(ref on_click) = handler.interaction_handler
-                {
-                    on_click(
-                        &mut self.state,
-                        &mut self.app_state,
-                        Interaction::Click(ClickState::Started, ClickLocation::new(point, *area)),
-                    );
-                } else if handler.interaction_type.drag
-                    && let Some(ref on_drag) = handler.interaction_handler
-                {
-                    on_drag(
-                        &mut self.state,
-                        &mut self.app_state,
-                        Interaction::Drag(DragState::Began {
-                            start: Point {
-                                x: point.x - area.x as f64,
-                                y: point.y - area.y as f64,
-                            },
-                            start_global: point,
-                        }),
-                    );
-                }
-                if let Some(ws) = self.windows.get_mut(&window_id) {
-                    ws.gesture_state = GestureState::Dragging {
-                        start: point,
-                        last_position: point,
-                        capturer: *capturer,
-                    };
-                }
-            }
-            if let Some(EditState { id, editor, .. }) = self.app_state.app_context.editor.as_mut()
-                && let Some(area) = self.app_state.app_context.editor_areas.get(id).cloned()
-                && area_contains_padded(&area, point, 10.)
-            {
-                editor.mouse_pressed(
-                    &mut self.app_state.app_context.layout_cx,
-                    &mut self.app_state.app_context.font_cx,
-                );
+                ws.window.request_redraw();
             }
+            ws.sync_cursor();
         }
+    }
 
-        if needs_redraw {
-            self.request_redraw_window(window_id);
+    pub(crate) fn mouse_pressed(
+        &mut self,
+        window_id: winit::window::WindowId,
+        button: MouseButton,
+    ) {
+        if let Some(ws) = self.windows.get_mut(&window_id)
+            && ws
+                .input
+                .mouse_pressed(&mut self.state, &mut self.app_state, button)
+        {
+            ws.window.request_redraw();
         }
     }
-    pub(crate) fn mouse_released(&mut self, window_id: winit::window::WindowId) {
-        let mut needs_redraw = false;
-        let cursor_position = self
-            .windows
-            .get(&window_id)
-            .and_then(|ws| ws.cursor_position);
-        let gesture_state = self
-            .windows
-            .get(&window_id)
-            .map(|ws| ws.gesture_state)
-            .unwrap_or(GestureState::None);
-        if let Some(current) = cursor_position {
-            if let Some(EditState { id, editor, .. }) = self.app_state.app_context.editor.as_mut()
-                && let Some(area) = self.app_state.app_context.editor_areas.get(id)
-            {
-                editor.mouse_released();
-                needs_redraw = true;
-                if !area_contains(area, current)
-                    && (!matches!(gesture_state, GestureState::Dragging { .. })
-                        || match gesture_state {
-                            GestureState::Dragging { capturer, .. } => capturer != *id,
-                            _ => false,
-                        })
-                {
-                    self.app_state.end_editing();
-                }
-            }
-            if let GestureState::Dragging {
-                start,
-                last_position,
-                capturer,
-            } = gesture_state
-            {
-                let distance = start.distance(current);
-                let delta = Point {
-                    x: current.x - last_position.x,
-                    y: current.y - last_position.y,
-                };
-                self.gesture_handlers(window_id)
-                    .iter()
-                    .filter(|(id, _, _)| *id == capturer)
-                    .for_each(|(_, area, gh)| {
-                        if let (Some(on_click), true) =
-                            (&gh.interaction_handler, gh.interaction_type.click)
-                        {
-                            needs_redraw = true;
-                            if area_contains(area, current) {
-                                on_click(
-                                    &mut self.state,
-                                    &mut self.app_state,
-                                    Interaction::Click(
-                                        ClickState::Completed,
-                                        ClickLocation::new(current, *area),
-                                    ),
-                                );
-                            } else {
-                                on_click(
-                                    &mut self.state,
-                                    &mut self.app_state,
-                                    Interaction::Click(
-                                        ClickState::Cancelled,
-                                        ClickLocation::new(current, *area),
-                                    ),
-                                );
-                            }
-                        }
-                        if let (Some(on_drag), true) =
-                            (&gh.interaction_handler, gh.interaction_type.drag)
-                        {
-                            needs_redraw = true;
-                            on_drag(
-                                &mut self.state,
-                                &mut self.app_state,
-                                Interaction::Drag(DragState::Completed {
-                                    start: Point {
-                                        x: start.x - area.x as f64,
-                                        y: start.y - area.y as f64,
-                                    },
-                                    current: Point {
-                                        x: current.x - area.x as f64,
-                                        y: current.y - area.y as f64,
-                                    },
-                                    start_global: start,
-                                    current_global: current,
-                                    delta,
-                                    distance: distance as f32,
-                                }),
-                            );
-                        }
-                    });
-            }
-            let press_start = match gesture_state {
-                GestureState::Dragging { start, .. } => Some(start),
-                _ => None,
-            };
-            for (_, area, handler) in self
-                .gesture_handlers(window_id)
-                .iter()
-                .filter(|(_, _, h)| h.interaction_type.click_outside)
-            {
-                if !area_contains_padded(area, current, 10.)
-                    && press_start.is_some_and(|s| !area_contains_padded(area, s, 10.))
-                    && let Some(ref handler) = handler.interaction_handler
-                {
-                    needs_redraw = true;
-                    handler(
-                        &mut self.state,
-                        &mut self.app_state,
-                        Interaction::ClickOutside(
-                            ClickState::Completed,
-                            ClickLocation::new(current, *area),
-                        ),
-                    );
-                }
-            }
-        }
-        if let Some(ws) = self.windows.get_mut(&window_id) {
-            ws.gesture_state = GestureState::None;
+
+    pub(crate) fn mouse_released(
+        &mut self,
+        window_id: winit::window::WindowId,
+        button: MouseButton,
+    ) {
+        let Some(ws) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        if ws
+            .input
+            .mouse_released(&mut self.state, &mut self.app_state, button)
+        {
+            ws.window.request_redraw();
         }
-        if needs_redraw {
-            self.request_redraw_window(window_id);
+        ws.sync_cursor();
+        if button != MouseButton::Left {
+            return;
         }
 
-        if let Some(ws) = self.windows.get(&window_id) {
-            if ws.fullscreen_requested
-                && ws.window.fullscreen() != Some(Fullscreen::Borderless(None))
-            {
-                ws.window
-                    .set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
-            } else if ws.window.fullscreen().is_some() {
-                ws.window.set_fullscreen(None);
-            }
+        if ws.fullscreen_requested && ws.window.fullscreen() != Some(Fullscreen::Borderless(None)) {
+            ws.window
+                .set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
+        } else if ws.window.fullscreen().is_some() {
+            ws.window.set_fullscreen(None);
         }
     }
 
-    pub(crate) fn scrolled(&mut self, window_id: winit::window::WindowId, delta: MouseScrollDelta) {
-        let mut needs_redraw = false;
-        let cursor_position = self
-            .windows
-            .get(&window_id)
-            .and_then(|ws| ws.cursor_position);
-        if let Some(current) = cursor_position
-            && let Some((_, _, handler)) =
-                self.gesture_handlers(window_id)
-                    .iter()
-                    .rev()
-                    .find(|(_, area, handler)| {
-                        area_contains(area, current) && (handler.interaction_type.scroll)
-                    })
-            && let Some(ref on_scroll) = handler.interaction_handler
+    pub(crate) fn scrolled(
+        &mut self,
+        window_id: winit::window::WindowId,
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+    ) {
+        let delta = match delta {
+            MouseScrollDelta::PixelDelta(pos) => {
+                MouseScrollDelta::PixelDelta(winit::dpi::PhysicalPosition::new(
+                    pos.x / self.app_state.app_context.scale_factor,
+                    pos.y / self.app_state.app_context.scale_factor,
+                ))
+            }
+            line => line,
+        };
+        if let Some(ws) = self.windows.get_mut(&window_id)
+            && ws
+                .input
+                .scrolled(&mut self.state, &mut self.app_state, delta, phase)
         {
-            needs_redraw = true;
-            on_scroll(
-                &mut self.state,
-                &mut self.app_state,
-                Interaction::Scroll(match delta {
-                    MouseScrollDelta::LineDelta(x, y) => ScrollDelta {
-                        x: x * 10.,
-                        y: y * 10.,
-                    },
-                    MouseScrollDelta::PixelDelta(physical_position) => ScrollDelta {
-                        x: physical_position.x as f32,
-                        y: physical_position.y as f32,
-                    },
-                }),
-            );
-        }
-        if needs_redraw {
-            self.request_redraw_window(window_id);
+            ws.window.request_redraw();
         }
     }
 }
@@ -1,13 +1,14 @@
-use crate::DEFAULT_FG;
 use crate::{
-    Binding, ClickState, DEFAULT_CORNER_ROUNDING, DEFAULT_FONT_SIZE, DEFAULT_PURP, adjust_brush,
+    Binding, ClickState, DEFAULT_CORNER_ROUNDING, DEFAULT_FONT_SIZE, DEFAULT_PURP, adjust_color,
     app::{AppCtx, AppState, View},
     rect,
 };
+use crate::{DEFAULT_FG, Transition};
 use backer::{Layout, nodes::stack};
 use std::rc::Rc;
 use vello_svg::vello::peniko::Brush;
 use vello_svg::vello::peniko::color::palette::css::TRANSPARENT;
+use winit::window::CursorIcon;
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ButtonState {
@@ -79,12 +80,13 @@ impl<'a, State> Button<'a, State> {
         let surface = if let Some(ref f) = surface_fn {
             f(btn_state, ctx)
         } else {
+            let fill = ctx.animate(
+                crate::id!(id),
+                adjust_color(DEFAULT_PURP, btn_state.depressed, btn_state.hovered),
+                Transition::default(),
+            );
             rect(crate::id!(id))
-                .fill(adjust_brush(
-                    &Brush::Solid(DEFAULT_PURP),
-                    btn_state.depressed,
-                    btn_state.hovered,
-                ))
+                .fill(Brush::Solid(fill))
                 .corner_rounding(DEFAULT_CORNER_ROUNDING)
                 .build(ctx)
         };
@@ -92,12 +94,13 @@ impl<'a, State> Button<'a, State> {
         let label = if let Some(ref f) = label_fn {
             f(btn_state, ctx)
         } else {
+            let fill = ctx.animate(
+                crate::id!(id),
+                adjust_color(DEFAULT_FG, btn_state.depressed, btn_state.hovered),
+                Transition::default(),
+            );
             crate::text(crate::id!(id), text_label.clone())
-                .fill(adjust_brush(
-                    &Brush::Solid(DEFAULT_FG),
-                    btn_state.depressed,
-                    btn_state.hovered,
-                ))
+                .fill(Brush::Solid(fill))
                 .font_size(DEFAULT_FONT_SIZE)
                 .view()
                 .finish(ctx)
@@ -109,6 +112,7 @@ impl<'a, State> Button<'a, State> {
             rect(crate::id!(id))
                 .fill(TRANSPARENT)
                 .view()
+                .cursor(CursorIcon::Pointer)
                 .on_hover({
                     let binding = self.binding.clone();
                     move |state, _app: &mut AppState, h| binding.update(state, |s| s.hovered = h)
@@ -132,3 +136,43 @@ impl<'a, State> Button<'a, State> {
         ])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone, Default)]
+    struct State {
+        button: ButtonState,
+        clicks: u32,
+    }
+
+    fn view<'a>(state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        button(crate::id!(), crate::binding!(state, State, button))
+            .text_label("Click")
+            .on_click(|state: &mut State, _app| state.clicks += 1)
+            .build(app.ctx())
+    }
+
+    #[test]
+    fn click_runs_on_click() {
+        let mut app = TestApp::new(State::default(), view).size(120., 40.);
+        app.click(60., 20.);
+        assert_eq!(app.state.clicks, 1);
+        assert!(app.state.button.hovered);
+        assert!(!app.state.button.depressed);
+        app.click(60., 20.);
+        assert_eq!(app.state.clicks, 2);
+    }
+
+    #[test]
+    fn press_depresses_until_release() {
+        let mut app = TestApp::new(State::default(), view).size(120., 40.);
+        app.move_pointer(60., 20.);
+        app.press();
+        assert!(app.state.button.depressed);
+        app.release();
+        assert!(!app.state.button.depressed);
+        assert_eq!(app.state.clicks, 1);
+    }
+}
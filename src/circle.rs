@@ -1,6 +1,7 @@
+use crate::Color;
 use crate::app::{AppCtx, View};
 use crate::background_style::BrushSource;
-use crate::shape::{PathData, circle_path};
+use crate::shape::{PathData, Shadow, ShadowShape, circle_path};
 use crate::view::{Drawable, DrawableType};
 
 use backer::Layout;
@@ -10,6 +11,8 @@ pub struct Circle {
     id: u64,
     fill: Option<BrushSource<()>>,
     stroke: Option<(BrushSource<()>, Stroke)>,
+    shadows: Vec<Shadow>,
+    backdrop_blur: Option<f32>,
 }
 
 pub fn circle(id: u64) -> Circle {
@@ -17,6 +20,8 @@ pub fn circle(id: u64) -> Circle {
         id,
         fill: None,
         stroke: None,
+        shadows: Vec::new(),
+        backdrop_blur: None,
     }
 }
 
@@ -29,12 +34,56 @@ impl Circle {
         self.stroke = Some((brush.into(), style));
         self
     }
+    /// Casts a shadow behind the shape, moved by `offset`. `blur_radius` softens its edge
+    /// and `spread` grows it.
+    pub fn shadow(
+        mut self,
+        color: Color,
+        offset: (f32, f32),
+        blur_radius: f32,
+        spread: f32,
+    ) -> Self {
+        self.shadows.push(Shadow {
+            color,
+            offset,
+            blur_radius,
+            spread,
+            inset: false,
+        });
+        self
+    }
+    /// Blurs whatever is drawn behind the shape, for frosted panels. Pair it with a
+    /// translucent fill.
+    pub fn backdrop_blur(mut self, radius: f32) -> Self {
+        self.backdrop_blur = Some(radius);
+        self
+    }
+    /// Shades the inside of the shape along its edges, as if it were cut into the surface.
+    pub fn inner_shadow(
+        mut self,
+        color: Color,
+        offset: (f32, f32),
+        blur_radius: f32,
+        spread: f32,
+    ) -> Self {
+        self.shadows.push(Shadow {
+            color,
+            offset,
+            blur_radius,
+            spread,
+            inset: true,
+        });
+        self
+    }
     pub(crate) fn into_path_data(self) -> PathData {
         PathData {
             id: self.id,
             builder: circle_path(),
             fill: self.fill,
             stroke: self.stroke,
+            shadows: self.shadows,
+            backdrop_blur: self.backdrop_blur,
+            shadow_shape: ShadowShape::Circle,
         }
     }
     pub fn view<State>(self) -> Drawable<State> {
@@ -0,0 +1,451 @@
+use crate::app::{AppState, View};
+use crate::image::Image;
+use crate::input::Placement;
+use crate::shape::{PathData, ShadowOp, backdrop_offsets};
+use crate::svg::Svg;
+use crate::view::DrawableType;
+use backer::Area;
+use image::RgbaImage;
+use parley::{Layout as ParleyLayout, PositionedLayoutItem};
+use std::collections::HashMap;
+use vello_cpu::peniko::{BlendMode, Compose, ImageSampler, Mix};
+use vello_cpu::{ImageSource, PaintType, Pixmap, RenderContext};
+use vello_svg::usvg;
+use vello_svg::util::{to_affine, to_bez_path, to_brush, to_stroke};
+use vello_svg::vello::kurbo::{Affine, Line, Point, Rect, RoundedRect, Shape as _, Size, Stroke};
+use vello_svg::vello::peniko::{Brush, Color, Fill};
+
+/// Rasterizes the draw list produced by `Layout::draw` on the CPU.
+///
+/// This mirrors the drawing done in `App::redraw` so that views can be rendered
+/// into an RGBA buffer on machines without a GPU.
+pub struct CpuRenderer {
+    svg_trees: HashMap<String, Option<usvg::Tree>>,
+    images: HashMap<u64, Option<(ImageSource, f64, f64)>>,
+}
+
+impl Default for CpuRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuRenderer {
+    pub fn new() -> Self {
+        Self {
+            svg_trees: HashMap::new(),
+            images: HashMap::new(),
+        }
+    }
+
+    /// Renders `views` into a `width` x `height` pixel image, using the scale factor of `app`.
+    pub fn render<State>(
+        &mut self,
+        views: &[View<State>],
+        app: &mut AppState,
+        width: u32,
+        height: u32,
+    ) -> RgbaImage {
+        let width = width.clamp(1, u16::MAX as u32) as u16;
+        let height = height.clamp(1, u16::MAX as u32) as u16;
+        let mut ctx = RenderContext::new(width, height);
+        self.draw_views(views, app, Affine::IDENTITY, false, &mut ctx);
+
+        ctx.flush();
+        let mut pixmap = Pixmap::new(width, height);
+        ctx.render_to_pixmap(&mut pixmap);
+        let bytes = pixmap
+            .take_unpremultiplied()
+            .into_iter()
+            .flat_map(|p| [p.r, p.g, p.b, p.a])
+            .collect();
+        RgbaImage::from_raw(width as u32, height as u32, bytes)
+            .expect("pixmap size matches image size")
+    }
+
+    /// Draws `views` prefixed with `base`, in physical pixels, and returns the number of clip
+    /// and blend layers left open. A `replay` redraws views behind a blurred backdrop, so it
+    /// doesn't record editor areas or blur again.
+    fn draw_views<State>(
+        &mut self,
+        views: &[View<State>],
+        app: &mut AppState,
+        mut base: Affine,
+        replay: bool,
+        ctx: &mut RenderContext,
+    ) -> usize {
+        let scale_factor = app.app_context.scale_factor;
+        let scale = Affine::scale(scale_factor);
+        let mut transform_stack = Vec::new();
+        let mut open_layers = 0;
+
+        for (index, item) in views.iter().enumerate() {
+            match item {
+                View::PushClip { path } => {
+                    ctx.set_transform(base * scale);
+                    ctx.set_fill_rule(Fill::NonZero);
+                    ctx.push_clip_layer(path);
+                    open_layers += 1;
+                }
+                View::PopClip | View::PopLayer => {
+                    ctx.pop_layer();
+                    open_layers -= 1;
+                }
+                View::PushLayer { path, alpha, blend } => {
+                    ctx.set_transform(base * scale);
+                    ctx.set_fill_rule(Fill::NonZero);
+                    ctx.push_layer(Some(path), Some(*blend), Some(*alpha), None, None);
+                    open_layers += 1;
+                }
+                View::PushTransform { transform } => {
+                    transform_stack.push(base);
+                    base = base * scale * *transform * scale.inverse();
+                }
+                View::PopTransform => base = transform_stack.pop().unwrap_or_default(),
+                View::EditorArea(id, area) => {
+                    if !replay {
+                        let placement = Placement {
+                            transform: scale.inverse() * base * scale,
+                            ..Placement::default()
+                        };
+                        app.app_context.editor_areas.insert(*id, (*area, placement));
+                    }
+                }
+                View::Draw { view, area, .. } => match view.as_ref() {
+                    DrawableType::Text(v) => {
+                        let (fill, layout, transform) = v.prepare(*area, *area, app);
+                        draw_layout(Some(&fill), base * transform, &layout, ctx);
+                    }
+                    DrawableType::Layout(boxed) => {
+                        let (layout, transform) = boxed.as_ref();
+                        draw_layout(None, base * *transform, layout, ctx);
+                    }
+                    DrawableType::Path(v) => {
+                        draw_shadows(v.shadow_ops(*area, false), base * scale, ctx);
+                        if let Some(radius) = v.backdrop_blur
+                            && !replay
+                        {
+                            let clip = (v.builder)(*area);
+                            ctx.set_transform(base * scale);
+                            ctx.set_fill_rule(Fill::EvenOdd);
+                            ctx.push_clip_layer(&clip);
+                            let offsets = backdrop_offsets(radius as f64, scale_factor);
+                            for (i, offset) in offsets.into_iter().enumerate() {
+                                ctx.push_layer(None, None, Some(1. / (i + 1) as f32), None, None);
+                                let backdrop = &views[..index];
+                                let reopened = self.draw_views(
+                                    backdrop,
+                                    app,
+                                    Affine::translate(offset),
+                                    true,
+                                    ctx,
+                                );
+                                for _ in 0..reopened {
+                                    ctx.pop_layer();
+                                }
+                                ctx.pop_layer();
+                            }
+                            ctx.pop_layer();
+                        }
+                        draw_path(v, *area, base * scale, ctx)
+                    }
+                    DrawableType::Svg(v) => self.draw_svg(v, *area, scale_factor, base, ctx),
+                    DrawableType::Image(v) => self.draw_image(v, *area, scale_factor, base, ctx),
+                },
+                View::Empty => (),
+            }
+        }
+        open_layers
+    }
+
+    fn draw_svg(
+        &mut self,
+        svg: &Svg,
+        area: Area,
+        scale_factor: f64,
+        base: Affine,
+        ctx: &mut RenderContext,
+    ) {
+        let tree = self
+            .svg_trees
+            .entry(svg.content.clone())
+            .or_insert_with(|| load_svg(&svg.content));
+        let Some(tree) = tree else {
+            return;
+        };
+        let size = tree.size();
+        let transform = svg.transform(
+            area,
+            size.width() as f64,
+            size.height() as f64,
+            scale_factor,
+        );
+        let bounds = Rect::from_origin_size(
+            Point::new(area.x as f64 * scale_factor, area.y as f64 * scale_factor),
+            Size::new(
+                area.width as f64 * scale_factor,
+                area.height as f64 * scale_factor,
+            ),
+        )
+        .to_path(0.1);
+
+        if svg.fill.is_some() {
+            ctx.set_transform(base);
+            ctx.set_fill_rule(Fill::NonZero);
+            ctx.push_clip_layer(&bounds);
+        }
+        draw_svg_group(tree.root(), base * transform, ctx);
+        if let Some(ref fill) = svg.fill {
+            ctx.set_transform(base);
+            ctx.push_layer(
+                Some(&bounds),
+                Some(BlendMode::new(Mix::Normal, Compose::SrcIn)),
+                None,
+                None,
+                None,
+            );
+            ctx.set_paint(paint_type(fill));
+            ctx.fill_path(&bounds);
+            ctx.pop_layer();
+            ctx.pop_layer();
+        }
+    }
+
+    fn draw_image(
+        &mut self,
+        image: &Image,
+        area: Area,
+        scale_factor: f64,
+        base: Affine,
+        ctx: &mut RenderContext,
+    ) {
+        let entry =
+            self.images
+                .entry(image.cache_key())
+                .or_insert_with(|| match image.load_image() {
+                    Ok(data) => Some((
+                        ImageSource::from_peniko_image_data(&data),
+                        data.width as f64,
+                        data.height as f64,
+                    )),
+                    Err(err) => {
+                        eprintln!("Loading image failed: {err}");
+                        None
+                    }
+                });
+        let Some((source, width, height)) = entry else {
+            return;
+        };
+        let (transform, scale) = image.transform(area, *width, *height, scale_factor);
+
+        ctx.set_transform(base * transform);
+        ctx.set_fill_rule(Fill::NonZero);
+        ctx.push_clip_layer(
+            &RoundedRect::from_origin_size(
+                Point::ZERO,
+                Size::new(*width, *height),
+                image.corner_rounding as f64 / scale,
+            )
+            .to_path(0.1),
+        );
+        ctx.set_paint(PaintType::Image(vello_cpu::Image {
+            image: source.clone(),
+            sampler: ImageSampler::default(),
+        }));
+        ctx.fill_rect(&Rect::new(0., 0., *width, *height));
+        ctx.pop_layer();
+    }
+}
+
+fn load_svg(content: &str) -> Option<usvg::Tree> {
+    match usvg::Tree::from_data(content.as_bytes(), &usvg::Options::default()) {
+        Err(err) => {
+            eprintln!("Loading svg failed: {err}");
+            None
+        }
+        Ok(tree) => Some(tree),
+    }
+}
+
+fn paint_type(brush: &Brush) -> PaintType {
+    match brush {
+        Brush::Solid(color) => PaintType::Solid(*color),
+        Brush::Gradient(gradient) => PaintType::Gradient(gradient.clone()),
+        // Image brushes are only produced by the gpu backend's own scenes.
+        Brush::Image(_) => PaintType::Solid(Color::TRANSPARENT),
+    }
+}
+
+/// `transform` maps logical coordinates to pixels. The outer shadows are drawn by the caller,
+/// beneath the blurred backdrop.
+fn draw_path(path: &PathData, area: Area, transform: Affine, ctx: &mut RenderContext) {
+    let user_path = (path.builder)(area);
+    ctx.set_transform(transform);
+    ctx.set_fill_rule(Fill::EvenOdd);
+
+    if path.fill.is_none() && path.stroke.is_none() {
+        ctx.set_paint(PaintType::Solid(Color::BLACK));
+        ctx.fill_path(&user_path);
+    } else {
+        if let Some(ref brush_source) = path.fill {
+            ctx.set_paint(paint_type(&brush_source.resolve(area, &())));
+            ctx.fill_path(&user_path);
+        }
+        draw_shadows(path.shadow_ops(area, true), transform, ctx);
+        ctx.set_transform(transform);
+        ctx.set_fill_rule(Fill::EvenOdd);
+        if let Some((ref brush_source, ref stroke_style)) = path.stroke {
+            ctx.set_paint(paint_type(&brush_source.resolve(area, &())));
+            ctx.set_stroke(stroke_style.clone());
+            ctx.stroke_path(&user_path);
+        }
+    }
+}
+
+/// Same shadows as `PathData::draw` draws on the gpu.
+fn draw_shadows(ops: Vec<ShadowOp>, transform: Affine, ctx: &mut RenderContext) {
+    ctx.set_transform(transform);
+    for op in ops {
+        match op {
+            ShadowOp::BlurredRect {
+                rect,
+                radius,
+                std_dev,
+                color,
+            } => {
+                ctx.set_paint(PaintType::Solid(color));
+                ctx.fill_blurred_rounded_rect(&rect, radius as f32, std_dev as f32);
+            }
+            ShadowOp::Fill(path, fill, color) => {
+                ctx.set_fill_rule(fill);
+                ctx.set_paint(PaintType::Solid(color));
+                ctx.fill_path(&path);
+            }
+            ShadowOp::Stroke(path, width, color) => {
+                ctx.set_paint(PaintType::Solid(color));
+                ctx.set_stroke(Stroke::new(width));
+                ctx.stroke_path(&path);
+            }
+            ShadowOp::PushClip(path, fill) => {
+                ctx.set_fill_rule(fill);
+                ctx.push_clip_layer(&path);
+            }
+            ShadowOp::PopClip => ctx.pop_layer(),
+        }
+    }
+}
+
+// Clip paths, masks and raster images inside svgs are not drawn by the cpu backend yet.
+fn draw_svg_group(group: &usvg::Group, transform: Affine, ctx: &mut RenderContext) {
+    let opacity = group.opacity().get();
+    if opacity < 1. {
+        ctx.push_opacity_layer(opacity);
+    }
+    for node in group.children() {
+        match node {
+            usvg::Node::Group(group) => draw_svg_group(group, transform, ctx),
+            usvg::Node::Text(text) => draw_svg_group(text.flattened(), transform, ctx),
+            usvg::Node::Path(path) => {
+                if !path.is_visible() {
+                    continue;
+                }
+                let local_path = to_bez_path(path);
+                ctx.set_transform(transform * to_affine(&path.abs_transform()));
+                if let Some(fill) = path.fill()
+                    && let Some((brush, brush_transform)) = to_brush(fill.paint(), fill.opacity())
+                {
+                    ctx.set_fill_rule(match fill.rule() {
+                        usvg::FillRule::NonZero => Fill::NonZero,
+                        usvg::FillRule::EvenOdd => Fill::EvenOdd,
+                    });
+                    ctx.set_paint(paint_type(&brush));
+                    ctx.set_paint_transform(brush_transform);
+                    ctx.fill_path(&local_path);
+                }
+                if let Some(stroke) = path.stroke()
+                    && let Some((brush, brush_transform)) =
+                        to_brush(stroke.paint(), stroke.opacity())
+                {
+                    ctx.set_paint(paint_type(&brush));
+                    ctx.set_paint_transform(brush_transform);
+                    ctx.set_stroke(to_stroke(stroke));
+                    ctx.stroke_path(&local_path);
+                }
+                ctx.reset_paint_transform();
+            }
+            usvg::Node::Image(_) => (),
+        }
+    }
+    if opacity < 1. {
+        ctx.pop_layer();
+    }
+}
+
+fn draw_layout(
+    fill: Option<&Brush>,
+    transform: Affine,
+    layout: &ParleyLayout<Brush>,
+    ctx: &mut RenderContext,
+) {
+    ctx.set_transform(transform);
+    for line in layout.lines() {
+        for item in line.items() {
+            let PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                continue;
+            };
+            let style = glyph_run.style();
+            let run_metrics = glyph_run.run().metrics();
+            let decoration_line = |y: f32| {
+                Line::new(
+                    (glyph_run.offset() as f64, y as f64),
+                    ((glyph_run.offset() + glyph_run.advance()) as f64, y as f64),
+                )
+                .to_path(0.1)
+            };
+            // Same painting order and metrics as `draw_layout::draw_layout`.
+            if let Some(underline) = &style.underline {
+                let offset = underline.offset.unwrap_or(run_metrics.underline_offset);
+                let width = underline.size.unwrap_or(run_metrics.underline_size);
+                let y = glyph_run.baseline() - offset + width / 2.;
+                ctx.set_paint(paint_type(&style.brush));
+                ctx.set_stroke(Stroke::new(width.into()));
+                ctx.stroke_path(&decoration_line(y));
+            }
+            let mut x = glyph_run.offset();
+            let y = glyph_run.baseline();
+            let run = glyph_run.run();
+            let synthesis = run.synthesis();
+
+            ctx.set_paint(paint_type(fill.unwrap_or(&style.brush)));
+            let mut builder = ctx
+                .glyph_run(run.font())
+                .font_size(run.font_size())
+                .hint(true)
+                .normalized_coords(run.normalized_coords());
+            if let Some(angle) = synthesis.skew() {
+                builder =
+                    builder.glyph_transform(Affine::skew(angle.to_radians().tan() as f64, 0.0));
+            }
+            builder.fill_glyphs(glyph_run.glyphs().map(|glyph| {
+                let gx = x + glyph.x;
+                let gy = y - glyph.y;
+                x += glyph.advance;
+                vello_cpu::Glyph {
+                    id: glyph.id as _,
+                    x: gx,
+                    y: gy,
+                }
+            }));
+            if let Some(strikethrough) = &style.strikethrough {
+                let offset = strikethrough
+                    .offset
+                    .unwrap_or(run_metrics.strikethrough_offset);
+                let width = strikethrough.size.unwrap_or(run_metrics.strikethrough_size);
+                let y = glyph_run.baseline() - offset + run_metrics.strikethrough_size / 2.;
+                ctx.set_paint(paint_type(&style.brush));
+                ctx.set_stroke(Stroke::new(width.into()));
+                ctx.stroke_path(&decoration_line(y));
+            }
+        }
+    }
+}
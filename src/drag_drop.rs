@@ -0,0 +1,321 @@
+use crate::app::{AppCtx, AppState, View};
+use crate::gestures::Interaction;
+use crate::input::InputState;
+use crate::{Binding, GestureState, Point, hit_test, scope};
+use backer::{Area, Layout};
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::rc::Rc;
+use winit::event::MouseButton;
+
+/// How far the pointer has to travel from the press before a drag source starts dragging.
+const DRAG_THRESHOLD: f64 = 4.;
+
+pub(crate) type PreviewFn<State> =
+    Rc<dyn Fn(&State, &mut AppCtx) -> Layout<'static, View<State>, AppCtx>>;
+
+/// What a drag source hands over when a drag begins.
+pub(crate) struct DragItem {
+    pub(crate) payload: Rc<dyn Any>,
+    pub(crate) type_id: TypeId,
+    /// A `PreviewFn<State>`, erased so it can be passed through `Interaction`.
+    pub(crate) preview: Box<dyn Any>,
+}
+
+/// Filled in by the drag source's handler when a drag begins.
+pub(crate) type DragSlot = Rc<RefCell<Option<DragItem>>>;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum DropEvent {
+    Enter(bool),
+    Over(Point),
+    Drop(Point),
+}
+
+/// A drag started from a `drag_source` view, following the pointer until release.
+pub(crate) struct DragSession<State> {
+    payload: Rc<dyn Any>,
+    type_id: TypeId,
+    preview: PreviewFn<State>,
+    /// The pointer position relative to the source view when the drag began.
+    grab: Point,
+    size: (f32, f32),
+    /// The drop target last sent `DropEvent::Enter(true)`.
+    target: Option<u64>,
+}
+
+/// Rewraps the preview of a drag started inside `scope` so it can be built from the root state.
+pub(crate) fn scope_drag_item<Root: 'static, Sub: 'static>(
+    slot: &DragSlot,
+    binding: Rc<Binding<Root, Sub>>,
+) {
+    let mut slot = slot.borrow_mut();
+    let Some(item) = slot.as_mut() else {
+        return;
+    };
+    let Some(preview) = item.preview.downcast_ref::<PreviewFn<Sub>>().cloned() else {
+        return;
+    };
+    let preview: PreviewFn<Root> =
+        Rc::new(move |root, ctx| scope(preview(&binding.get(root), ctx), binding.as_ref().clone()));
+    item.preview = Box::new(preview);
+}
+
+impl<State: 'static> InputState<State> {
+    /// Starts a drag once the pointer has moved far enough from a press on a drag source,
+    /// then keeps the drop target under the pointer up to date.
+    pub(crate) fn drag_moved(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        pos: Point,
+    ) -> bool {
+        let GestureState::Dragging {
+            start,
+            capturer,
+            button: MouseButton::Left,
+            ..
+        } = self.gesture_state
+        else {
+            return false;
+        };
+        if self.drag_session.is_none() && start.distance(pos) >= DRAG_THRESHOLD {
+            self.begin_drag(state, app_state, capturer, start);
+        }
+        if self.drag_session.is_none() {
+            return false;
+        }
+        self.update_drop_target(state, app_state, pos);
+        true
+    }
+
+    fn begin_drag(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        source: u64,
+        start: Point,
+    ) {
+        let slot: DragSlot = Rc::new(RefCell::new(None));
+        let mut source_area = None;
+        for (id, area, handler, placement) in self.gesture_handlers.clone() {
+            if id == source
+                && handler.interaction_type.drag_source
+                && let Some(ref on_drag_source) = handler.interaction_handler
+            {
+                on_drag_source(state, app_state, Interaction::DragSource(slot.clone()));
+                source_area = Some((area, placement));
+                break;
+            }
+        }
+        let (Some(item), Some((area, placement))) = (slot.take(), source_area) else {
+            return;
+        };
+        let Ok(preview) = item.preview.downcast::<PreviewFn<State>>() else {
+            return;
+        };
+        self.drag_session = Some(DragSession {
+            payload: item.payload,
+            type_id: item.type_id,
+            preview: *preview,
+            grab: placement.local(&area, start),
+            size: (area.width, area.height),
+            target: None,
+        });
+    }
+
+    fn update_drop_target(&mut self, state: &mut State, app_state: &mut AppState, pos: Point) {
+        let Some(session) = self.drag_session.as_mut() else {
+            return;
+        };
+        let type_id = session.type_id;
+        let target = self
+            .gesture_handlers
+            .iter()
+            .rev()
+            .find(|(_, area, handler, placement)| {
+                handler.interaction_type.drop_target == Some(type_id)
+                    && hit_test(area, placement, pos)
+            })
+            .map(|(id, _, _, _)| *id);
+        let previous = std::mem::replace(&mut session.target, target);
+        if previous != target {
+            if let Some(previous) = previous {
+                self.send_drop_event(state, app_state, previous, DropEvent::Enter(false));
+            }
+            if let Some(target) = target {
+                self.send_drop_event(state, app_state, target, DropEvent::Enter(true));
+            }
+        }
+        if let Some(target) = target {
+            self.send_drop_event(state, app_state, target, DropEvent::Over(pos));
+        }
+    }
+
+    /// Drops the payload on the target under `pos`. Returns whether a drag was in progress.
+    pub(crate) fn end_drag(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        pos: Point,
+    ) -> bool {
+        if self.drag_session.is_none() {
+            return false;
+        }
+        self.update_drop_target(state, app_state, pos);
+        if let Some(DragSession {
+            target: Some(target),
+            ..
+        }) = self.drag_session
+        {
+            self.send_drop_event(state, app_state, target, DropEvent::Drop(pos));
+            self.send_drop_event(state, app_state, target, DropEvent::Enter(false));
+        }
+        self.drag_session = None;
+        true
+    }
+
+    /// Positions are global; they are made local to each target's area here.
+    fn send_drop_event(
+        &self,
+        state: &mut State,
+        app_state: &mut AppState,
+        target: u64,
+        event: DropEvent,
+    ) {
+        let Some(session) = self.drag_session.as_ref() else {
+            return;
+        };
+        for (id, area, handler, placement) in self.gesture_handlers.clone() {
+            if id == target
+                && handler.interaction_type.drop_target == Some(session.type_id)
+                && let Some(ref on_drop_target) = handler.interaction_handler
+            {
+                let local = |p: Point| placement.local(&area, p);
+                let event = match event {
+                    DropEvent::Enter(entered) => DropEvent::Enter(entered),
+                    DropEvent::Over(p) => DropEvent::Over(local(p)),
+                    DropEvent::Drop(p) => DropEvent::Drop(local(p)),
+                };
+                on_drop_target(
+                    state,
+                    app_state,
+                    Interaction::DropTarget(event, session.payload.clone()),
+                );
+            }
+        }
+    }
+
+    /// The views of the floating drag preview, positioned under the pointer where the
+    /// source was grabbed. They are drawn above the frame and never receive input.
+    pub(crate) fn drag_preview(&self, state: &State, ctx: &mut AppCtx) -> Vec<View<State>> {
+        let (Some(session), Some(pos)) = (self.drag_session.as_ref(), self.cursor_position) else {
+            return Vec::new();
+        };
+        let mut layout = (session.preview)(state, ctx);
+        layout
+            .draw(
+                Area {
+                    x: (pos.x - session.grab.x) as f32,
+                    y: (pos.y - session.grab.y) as f32,
+                    width: session.size.0,
+                    height: session.size.1,
+                },
+                ctx,
+            )
+            .into_iter()
+            .map(|view| match view {
+                View::Draw { view, area, .. } => View::Draw {
+                    view,
+                    gesture_handlers: Vec::new(),
+                    area,
+                },
+                View::EditorArea(..) => View::Empty,
+                view => view,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Default)]
+    struct State {
+        entered: Vec<bool>,
+        dropped: Vec<(String, Point)>,
+        numbers: Vec<u32>,
+    }
+
+    fn view<'a>(_state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        row(vec![
+            rect(crate::id!())
+                .fill(TRANSPARENT)
+                .view()
+                .drop_target(
+                    |state: &mut State, _app, entered| state.entered.push(entered),
+                    |_state, _app, _payload: &String, _point| (),
+                    |state, _app, payload, point| state.dropped.push((payload, point)),
+                )
+                .finish(app.ctx()),
+            rect(crate::id!())
+                .fill(TRANSPARENT)
+                .view()
+                .drag_source(
+                    |_state: &State| "card".to_string(),
+                    |_state, ctx| rect(crate::id!()).fill(TRANSPARENT).view().finish(ctx),
+                )
+                .finish(app.ctx()),
+            rect(crate::id!())
+                .fill(TRANSPARENT)
+                .view()
+                .drop_target(
+                    |state: &mut State, _app, entered| state.entered.push(entered),
+                    |_state, _app, _payload: &u32, _point| (),
+                    |state, _app, payload, _point| state.numbers.push(payload),
+                )
+                .finish(app.ctx()),
+        ])
+        .pad(50.)
+    }
+
+    // The window is 1044 wide, so each of the three views is about 315 wide
+    const STRING_TARGET: (f64, f64) = (200., 400.);
+    const SOURCE: (f64, f64) = (520., 400.);
+    const NUMBER_TARGET: (f64, f64) = (840., 400.);
+
+    #[test]
+    fn payload_is_dropped_on_a_matching_target() {
+        let mut app = TestApp::new(State::default(), view);
+        app.drag(SOURCE, STRING_TARGET, 5);
+        assert_eq!(app.state.entered, vec![true, false]);
+        let [(payload, point)] = app.state.dropped.as_slice() else {
+            panic!("expected one drop, got {:?}", app.state.dropped);
+        };
+        assert_eq!(payload, "card");
+        // Drop positions are relative to the target
+        assert_eq!(point.y, 350.);
+        assert!(point.x > 0. && point.x < 315.);
+    }
+
+    #[test]
+    fn targets_for_another_payload_type_are_ignored() {
+        let mut app = TestApp::new(State::default(), view);
+        app.drag(SOURCE, NUMBER_TARGET, 5);
+        assert!(app.state.entered.is_empty());
+        assert!(app.state.dropped.is_empty());
+        assert!(app.state.numbers.is_empty());
+    }
+
+    #[test]
+    fn dropping_outside_every_target_cancels_the_drag() {
+        let mut app = TestApp::new(State::default(), view);
+        app.drag(SOURCE, (520., 780.), 5);
+        assert!(app.state.dropped.is_empty());
+        // The drag is over, so passing over a target afterwards doesn't enter it
+        app.move_pointer(STRING_TARGET.0, STRING_TARGET.1);
+        assert!(app.state.entered.is_empty());
+        assert!(app.state.dropped.is_empty());
+    }
+}
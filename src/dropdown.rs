@@ -4,6 +4,7 @@ use crate::{Color, TRANSPARENT};
 use backer::{Align, Layout, nodes::*};
 use std::rc::Rc;
 use vello_svg::vello::kurbo::Stroke;
+use winit::window::CursorIcon;
 
 #[derive(Debug, Clone)]
 pub struct DropdownState<T> {
@@ -108,6 +109,7 @@ impl<'a, State, T: Clone + PartialEq + 'static> DropDown<'a, State, T> {
                     rect(crate::id!(index as u64, id))
                         .fill(TRANSPARENT)
                         .view()
+                        .cursor(CursorIcon::Pointer)
                         .on_click({
                             let binding = binding.clone();
                             let on_select = on_select.clone();
@@ -10,13 +10,12 @@ use winit::{event::Modifiers, keyboard::NamedKey};
 pub use parley::editing::Generation;
 use parley::{FontContext, LayoutContext, PlainEditor, PlainEditorDriver};
 
-use crate::Key;
+use crate::shortcut::primary_modifier;
+use crate::{Key, KeyEvent};
 
 #[derive(Clone)]
 pub struct Editor {
     pub(crate) editor: PlainEditor<Brush>,
-    pub(crate) last_click_time: Option<Instant>,
-    pub(crate) click_count: u32,
     pub(crate) pointer_down: bool,
     pub(crate) cursor_pos: (f32, f32),
     pub(crate) cursor_visible: bool,
@@ -38,8 +37,6 @@ impl Editor {
         styles.insert(StyleProperty::Brush(palette::css::WHITE.into()));
         Self {
             editor,
-            last_click_time: Default::default(),
-            click_count: Default::default(),
             pointer_down: Default::default(),
             cursor_pos: Default::default(),
             cursor_visible: Default::default(),
@@ -276,26 +273,39 @@ impl Editor {
             _ => (),
         }
     }
+    /// Whether `handle_key` does something with `event`, so it shouldn't trigger shortcuts.
+    pub(crate) fn consumes(&self, event: &KeyEvent) -> bool {
+        let primary = event.modifiers.intersects(primary_modifier());
+        match &event.key {
+            Key::Character(c) if primary => {
+                matches!(c.to_lowercase().as_str(), "c" | "x" | "v" | "a")
+            }
+            Key::Character(_) => !event.modifiers.control_key() && !event.modifiers.super_key(),
+            Key::Named(
+                NamedKey::ArrowLeft
+                | NamedKey::ArrowRight
+                | NamedKey::ArrowUp
+                | NamedKey::ArrowDown
+                | NamedKey::Home
+                | NamedKey::End
+                | NamedKey::Delete
+                | NamedKey::Backspace
+                | NamedKey::Enter
+                | NamedKey::Space,
+            ) => true,
+            Key::Named(_) => false,
+        }
+    }
+    /// `click_count` is the number of consecutive clicks, as counted by `ClickCounter`.
     pub(crate) fn mouse_pressed(
         &mut self,
         layout_cx: &mut LayoutContext<Brush>,
         font_cx: &mut FontContext,
+        click_count: u32,
     ) {
         self.cursor_reset();
         if !self.editor.is_composing() {
             self.pointer_down = true;
-            let now = Instant::now();
-            if let Some(last) = self.last_click_time.take() {
-                if now.duration_since(last).as_secs_f64() < 0.25 {
-                    self.click_count = (self.click_count + 1) % 4;
-                } else {
-                    self.click_count = 1;
-                }
-            } else {
-                self.click_count = 1;
-            }
-            self.last_click_time = Some(now);
-            let click_count = self.click_count;
             let cursor_pos = self.cursor_pos;
             let mut drv = self.editor.driver(font_cx, layout_cx);
             match click_count {
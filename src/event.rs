@@ -1,10 +1,7 @@
 use std::path::PathBuf;
 
 use vello_svg::vello::kurbo::Point;
-use winit::{
-    event::{ElementState, Modifiers, MouseButton, MouseScrollDelta, TouchPhase},
-    keyboard::Key,
-};
+use winit::event::{ElementState, KeyEvent, Modifiers, MouseButton, MouseScrollDelta, TouchPhase};
 
 /// The event associated with a touch at a single point.
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -44,10 +41,10 @@ pub enum WindowEvent {
     Moved(Point),
 
     /// The given keyboard key was pressed.
-    KeyPressed(Key),
+    KeyPressed(KeyEvent),
 
     /// The given keyboard key was released.
-    KeyReleased(Key),
+    KeyReleased(KeyEvent),
 
     /// The mouse moved to the given x, y position.
     MouseMoved(Point),
@@ -89,6 +86,15 @@ pub enum WindowEvent {
     /// is being pressed) and stage (integer representing the click level).
     TouchPressure(TouchpadPressure),
 
+    /// A trackpad pinch, with the change in magnification.
+    PinchGesture(f64, TouchPhase),
+
+    /// A trackpad rotation, with the change in degrees, counterclockwise.
+    RotationGesture(f32, TouchPhase),
+
+    /// A two-finger trackpad pan, with the change in position.
+    PanGesture(Point, TouchPhase),
+
     /// The window gained focus.
     Focused,
 
@@ -194,8 +200,8 @@ impl WindowEvent {
             }),
 
             winit::event::WindowEvent::KeyboardInput { event, .. } => match event.state {
-                ElementState::Pressed => KeyPressed(event.logical_key),
-                ElementState::Released => KeyReleased(event.logical_key),
+                ElementState::Pressed => KeyPressed(event),
+                ElementState::Released => KeyReleased(event),
             },
 
             winit::event::WindowEvent::ModifiersChanged(modifiers) => ModifiersChanged(modifiers),
@@ -215,10 +221,23 @@ impl WindowEvent {
             winit::event::WindowEvent::Ime(_)
             | winit::event::WindowEvent::Occluded(_)
             | winit::event::WindowEvent::ActivationTokenDone { .. }
-            | winit::event::WindowEvent::PinchGesture { .. }
-            | winit::event::WindowEvent::PanGesture { .. }
-            | winit::event::WindowEvent::DoubleTapGesture { .. }
-            | winit::event::WindowEvent::RotationGesture { .. } => return None,
+            | winit::event::WindowEvent::DoubleTapGesture { .. } => return None,
+
+            winit::event::WindowEvent::PinchGesture { delta, phase, .. } => {
+                PinchGesture(delta, phase)
+            }
+
+            winit::event::WindowEvent::RotationGesture { delta, phase, .. } => {
+                RotationGesture(delta, phase)
+            }
+
+            winit::event::WindowEvent::PanGesture { delta, phase, .. } => PanGesture(
+                Point {
+                    x: delta.x as f64,
+                    y: delta.y as f64,
+                },
+                phase,
+            ),
         };
 
         Some(event)
@@ -1,10 +1,19 @@
-use backer::Area;
-
-use crate::{Key, Point};
+use crate::drag_drop::{DragSlot, DropEvent};
+use crate::{Insets, KeyEvent, MouseButton, Point, TouchPhase};
 use std::{
+    any::{Any, TypeId},
     fmt::{self, Debug, Formatter},
+    path::PathBuf,
     rc::Rc,
+    time::{Duration, Instant},
 };
+use winit::window::CursorIcon;
+
+#[cfg(target_os = "linux")]
+const MULTI_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+#[cfg(not(target_os = "linux"))]
+const MULTI_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+const MULTI_CLICK_DISTANCE: f64 = 4.;
 
 #[derive(Debug, Clone, Copy)]
 pub enum GestureState {
@@ -13,6 +22,7 @@ pub enum GestureState {
         start: Point,
         last_position: Point,
         capturer: u64,
+        button: MouseButton,
     },
 }
 
@@ -50,21 +60,57 @@ pub enum ClickState {
 #[derive(Debug, Clone, Copy)]
 pub struct ClickLocation {
     global: Point,
-    area: Area,
+    local: Point,
+    button: MouseButton,
+    count: u32,
 }
 
 impl ClickLocation {
-    pub(crate) fn new(global: Point, area: Area) -> Self {
-        ClickLocation { global, area }
+    pub(crate) fn new(global: Point, local: Point, button: MouseButton, count: u32) -> Self {
+        ClickLocation {
+            global,
+            local,
+            button,
+            count,
+        }
+    }
+    pub fn button(&self) -> MouseButton {
+        self.button
+    }
+    /// 1 for a single click, 2 for a double click and so on.
+    pub fn count(&self) -> u32 {
+        self.count
     }
     pub fn global(&self) -> Point {
         self.global
     }
+    /// The position relative to the view, in the view's own coordinates when it is transformed.
     pub fn local(&self) -> Point {
-        Point {
-            x: self.global.x - self.area.x as f64,
-            y: self.global.y - self.area.y as f64,
-        }
+        self.local
+    }
+}
+
+/// Counts consecutive presses of the same button that land close together in time and space.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ClickCounter {
+    last_press: Option<(Instant, Point, MouseButton)>,
+    pub(crate) count: u32,
+}
+
+impl ClickCounter {
+    pub(crate) fn press(&mut self, point: Point, button: MouseButton, now: Instant) -> u32 {
+        self.count = match self.last_press {
+            Some((time, last_point, last_button))
+                if last_button == button
+                    && now.duration_since(time) <= MULTI_CLICK_INTERVAL
+                    && last_point.distance(point) <= MULTI_CLICK_DISTANCE =>
+            {
+                self.count + 1
+            }
+            _ => 1,
+        };
+        self.last_press = Some((now, point, button));
+        self.count
     }
 }
 
@@ -73,8 +119,19 @@ pub(crate) enum Interaction {
     ClickOutside(ClickState, ClickLocation),
     Drag(DragState),
     Hover(bool),
-    Key(Key),
+    Key(KeyEvent),
+    Focus(bool),
     Scroll(ScrollDelta),
+    Pinch(Pinch),
+    Rotate(Rotation),
+    Pan(Pan),
+    FileHover(FileDragState),
+    FileDrop(Vec<PathBuf>, Point),
+    DragSource(DragSlot),
+    DropTarget(DropEvent, Rc<dyn Any>),
+    LongPress(ClickLocation),
+    HoverIntent(bool),
+    Zoom(ZoomEvent),
 }
 
 #[derive(Debug, Clone)]
@@ -86,16 +143,192 @@ pub enum EditInteraction {
 #[derive(Debug, Default, Clone, Copy)]
 pub(crate) struct InteractionType {
     pub(crate) click: bool,
+    pub(crate) secondary_click: bool,
+    pub(crate) middle_click: bool,
     pub(crate) click_outside: bool,
     pub(crate) drag: bool,
     pub(crate) hover: bool,
     pub(crate) key: bool,
+    pub(crate) global_key: bool,
+    pub(crate) focus: bool,
+    pub(crate) focusable: bool,
     pub(crate) scroll: bool,
+    pub(crate) pinch: bool,
+    pub(crate) rotate: bool,
+    pub(crate) pan: bool,
+    pub(crate) file_hover: bool,
+    pub(crate) file_drop: bool,
+    pub(crate) drag_source: bool,
+    /// The payload type accepted by a drop target.
+    pub(crate) drop_target: Option<TypeId>,
+    /// Pointer events handled by the view also reach the views below it.
+    pub(crate) pass_through: bool,
+    pub(crate) cursor: Option<CursorIcon>,
+    pub(crate) hit_slop: Option<Insets>,
+    /// How long the pointer has to be held down without moving.
+    pub(crate) long_press: Option<Duration>,
+    /// How long the pointer has to rest on the view.
+    pub(crate) hover_intent: Option<Duration>,
+    pub(crate) zoom: bool,
+}
+
+impl InteractionType {
+    pub(crate) fn clicks(&self, button: MouseButton) -> bool {
+        match button {
+            MouseButton::Left => self.click,
+            MouseButton::Right => self.secondary_click,
+            MouseButton::Middle => self.middle_click,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn handles_press(&self, button: MouseButton) -> bool {
+        self.clicks(button)
+            || (button == MouseButton::Left
+                && (self.drag || self.drag_source || self.long_press.is_some()))
+    }
+
+    /// Whether the handler takes pointer input at its area, covering the views below for hover
+    /// and cursor. Wheel scrolling and zooming are routed on their own and don't cover.
+    pub(crate) fn is_pointer_target(&self) -> bool {
+        self.click
+            || self.secondary_click
+            || self.middle_click
+            || self.drag
+            || self.hover
+            || self.pinch
+            || self.rotate
+            || self.pan
+            || self.file_hover
+            || self.file_drop
+            || self.drag_source
+            || self.drop_target.is_some()
+            || self.long_press.is_some()
+            || self.hover_intent.is_some()
+    }
+
+    /// Whether the view can hold keyboard focus and is a Tab stop.
+    pub(crate) fn takes_focus(&self) -> bool {
+        self.focusable || self.key
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &'static str> {
+        [
+            (self.click, "click"),
+            (self.secondary_click, "secondary_click"),
+            (self.middle_click, "middle_click"),
+            (self.click_outside, "click_outside"),
+            (self.drag, "drag"),
+            (self.hover, "hover"),
+            (self.key, "key"),
+            (self.global_key, "global_key"),
+            (self.focus, "focus"),
+            (self.focusable, "focusable"),
+            (self.scroll, "scroll"),
+            (self.pinch, "pinch"),
+            (self.rotate, "rotate"),
+            (self.pan, "pan"),
+            (self.file_hover, "file_hover"),
+            (self.file_drop, "file_drop"),
+            (self.drag_source, "drag_source"),
+            (self.drop_target.is_some(), "drop_target"),
+            (self.pass_through, "pass_through"),
+            (self.cursor.is_some(), "cursor"),
+            (self.hit_slop.is_some(), "hit_slop"),
+            (self.long_press.is_some(), "long_press"),
+            (self.hover_intent.is_some(), "hover_intent"),
+            (self.zoom, "zoom"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, name)| enabled.then_some(name))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GesturePhase {
+    Began,
+    Changed,
+    Ended,
+    Cancelled,
+}
+
+/// A two-finger pinch from a touch screen or trackpad.
+#[derive(Debug, Clone, Copy)]
+pub struct Pinch {
+    pub phase: GesturePhase,
+    /// Scale relative to the start of the gesture.
+    pub scale: f64,
+    /// Scale relative to the previous event.
+    pub delta: f64,
+    /// The center of the gesture, relative to the view.
+    pub center: Point,
 }
 
+/// A two-finger rotation from a touch screen or trackpad.
+#[derive(Debug, Clone, Copy)]
+pub struct Rotation {
+    pub phase: GesturePhase,
+    /// Clockwise rotation in radians since the start of the gesture.
+    pub angle: f64,
+    /// Clockwise rotation in radians since the previous event.
+    pub delta: f64,
+    /// The center of the gesture, relative to the view.
+    pub center: Point,
+}
+
+/// A two-finger pan from a touch screen or trackpad.
+#[derive(Debug, Clone, Copy)]
+pub struct Pan {
+    pub phase: GesturePhase,
+    /// Translation since the start of the gesture.
+    pub translation: Point,
+    /// Translation since the previous event.
+    pub delta: Point,
+    /// The center of the gesture, relative to the view.
+    pub center: Point,
+}
+
+/// A zoom about a point, from a pinch on a trackpad or touch screen or from Ctrl+wheel.
+#[derive(Debug, Clone, Copy)]
+pub struct ZoomEvent {
+    /// Ctrl+wheel zooms are reported as single `Changed` events.
+    pub phase: GesturePhase,
+    /// Scale relative to the previous event. Multiply the current zoom by it.
+    pub delta: f64,
+    /// The point to zoom about, relative to the view.
+    pub focus: Point,
+}
+
+/// Files dragged from the OS over a view.
+#[derive(Debug, Clone)]
+pub enum FileDragState {
+    /// Sent when the drag enters the view and whenever the pointer moves over it.
+    /// `position` is in window coordinates.
+    Hovered {
+        paths: Vec<PathBuf>,
+        position: Point,
+    },
+    /// The drag left the view, was cancelled or the files were dropped.
+    Exited,
+}
+
+/// A scroll in logical pixels. Line based deltas from a mouse wheel are converted to pixels.
+#[derive(Debug, Clone, Copy)]
 pub struct ScrollDelta {
     pub x: f32,
     pub y: f32,
+    /// `Started` and `Ended` bracket a trackpad gesture. Mouse wheels only report `Moved`.
+    pub phase: TouchPhase,
+    pub unit: ScrollUnit,
+}
+
+/// What a scroll delta was originally measured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollUnit {
+    /// Lines from a notched mouse wheel.
+    Line,
+    /// Pixels from a trackpad or smooth scrolling wheel.
+    Pixel,
 }
 pub(crate) type InteractionHandler<T, U> = Rc<dyn Fn(&mut T, &mut U, Interaction)>;
 pub struct GestureHandler<T, U> {
@@ -128,3 +361,72 @@ impl<T, U> Clone for GestureHandler<T, U> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppCtx, AppState, Layout, View};
+
+    #[test]
+    fn click_counter_counts_nearby_presses() {
+        let mut counter = ClickCounter::default();
+        let point = Point::new(10., 10.);
+        let now = Instant::now();
+        assert_eq!(counter.press(point, MouseButton::Left, now), 1);
+        assert_eq!(counter.press(point, MouseButton::Left, now), 2);
+        assert_eq!(
+            counter.press(Point::new(11., 9.), MouseButton::Left, now),
+            3
+        );
+    }
+
+    #[test]
+    fn click_counter_restarts_on_another_button_or_position() {
+        let mut counter = ClickCounter::default();
+        let point = Point::new(10., 10.);
+        let now = Instant::now();
+        counter.press(point, MouseButton::Left, now);
+        assert_eq!(counter.press(point, MouseButton::Right, now), 1);
+        assert_eq!(
+            counter.press(Point::new(100., 10.), MouseButton::Right, now),
+            1
+        );
+    }
+
+    #[test]
+    fn click_counter_restarts_after_the_interval() {
+        let mut counter = ClickCounter::default();
+        let point = Point::new(10., 10.);
+        let now = Instant::now();
+        counter.press(point, MouseButton::Left, now);
+        assert_eq!(
+            counter.press(point, MouseButton::Left, now + MULTI_CLICK_INTERVAL),
+            2
+        );
+        let late = now + MULTI_CLICK_INTERVAL * 2 + Duration::from_millis(1);
+        assert_eq!(counter.press(point, MouseButton::Left, late), 1);
+    }
+
+    fn view<'a>(_state: &'a Vec<u32>, app: &mut AppState) -> Layout<'a, View<Vec<u32>>, AppCtx> {
+        crate::rect(crate::id!())
+            .fill(crate::TRANSPARENT)
+            .view()
+            .on_click(|counts: &mut Vec<u32>, _app, click_state, location| {
+                if click_state == ClickState::Completed {
+                    counts.push(location.count());
+                }
+            })
+            .finish(app.ctx())
+    }
+
+    #[test]
+    fn multi_clicks_follow_the_app_clock() {
+        let mut app = crate::TestApp::new(Vec::new(), view).size(100., 100.);
+        app.click(50., 50.);
+        app.advance(Duration::from_millis(100));
+        app.click(50., 50.);
+        app.advance(MULTI_CLICK_INTERVAL * 2);
+        app.click(50., 50.);
+        assert_eq!(app.state, [1, 2, 1]);
+    }
+}
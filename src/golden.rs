@@ -0,0 +1,210 @@
+use image::{Rgba, RgbaImage};
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Set to overwrite stored snapshots with the rendered images instead of comparing.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "HAVEN_UPDATE_SNAPSHOTS";
+
+/// Renders a `TestApp` with a fresh `CpuRenderer` and compares the result to a png.
+///
+/// Relative paths are resolved against the calling crate's manifest directory. The
+/// optional third argument is the per-channel tolerance, defaulting to 2.
+///
+/// ```ignore
+/// let mut app = TestApp::new(State::default(), view).size(200., 60.).scale_factor(2.);
+/// assert_snapshot!(app, "tests/snapshots/button.png");
+/// ```
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($app:expr, $path:expr) => {
+        $crate::assert_snapshot!($app, $path, 2)
+    };
+    ($app:expr, $path:expr, $tolerance:expr) => {{
+        let image = $app.render(&mut $crate::CpuRenderer::new());
+        let path = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path);
+        if let Err(err) = $crate::compare_snapshot(&image, &path, $tolerance) {
+            panic!("{err}");
+        }
+    }};
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(PathBuf, String),
+    Missing {
+        path: PathBuf,
+        actual_path: PathBuf,
+    },
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+        actual_path: PathBuf,
+    },
+    PixelMismatch {
+        mismatched: usize,
+        max_difference: u8,
+        actual_path: PathBuf,
+        diff_path: PathBuf,
+    },
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(path, err) => write!(f, "snapshot {}: {err}", path.display()),
+            SnapshotError::Missing { path, actual_path } => write!(
+                f,
+                "snapshot {} is missing (actual written to {}), set {UPDATE_SNAPSHOTS_VAR}=1 to record it",
+                path.display(),
+                actual_path.display()
+            ),
+            SnapshotError::SizeMismatch {
+                expected,
+                actual,
+                actual_path,
+            } => write!(
+                f,
+                "snapshot size mismatch: expected {}x{}, got {}x{} (actual written to {})",
+                expected.0,
+                expected.1,
+                actual.0,
+                actual.1,
+                actual_path.display()
+            ),
+            SnapshotError::PixelMismatch {
+                mismatched,
+                max_difference,
+                actual_path,
+                diff_path,
+            } => write!(
+                f,
+                "snapshot mismatch: {mismatched} pixels differ by up to {max_difference} (actual written to {}, diff to {})",
+                actual_path.display(),
+                diff_path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Compares `image` to the png at `path`, allowing each channel to differ by `tolerance`.
+///
+/// When `HAVEN_UPDATE_SNAPSHOTS` is set the snapshot is written instead and treated as a
+/// match. Otherwise a missing snapshot fails, and on mismatch the rendered image and a
+/// diff highlighting differing pixels in red are written next to the snapshot as
+/// `<name>.actual.png` and `<name>.diff.png`.
+pub fn compare_snapshot(
+    image: &RgbaImage,
+    path: impl AsRef<Path>,
+    tolerance: u8,
+) -> Result<(), SnapshotError> {
+    let path = path.as_ref();
+    let actual_path = sibling(path, "actual");
+    let diff_path = sibling(path, "diff");
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| SnapshotError::Io(parent.to_path_buf(), err.to_string()))?;
+    }
+
+    if std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        save(image, path)?;
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+
+    if !path.exists() {
+        save(image, &actual_path)?;
+        return Err(SnapshotError::Missing {
+            path: path.to_path_buf(),
+            actual_path,
+        });
+    }
+
+    let expected = image::open(path)
+        .map_err(|err| SnapshotError::Io(path.to_path_buf(), err.to_string()))?
+        .to_rgba8();
+
+    if expected.dimensions() != image.dimensions() {
+        save(image, &actual_path)?;
+        return Err(SnapshotError::SizeMismatch {
+            expected: expected.dimensions(),
+            actual: image.dimensions(),
+            actual_path,
+        });
+    }
+
+    let mut mismatched = 0;
+    let mut max_difference = 0;
+    let diff = RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let a = image.get_pixel(x, y);
+        let b = expected.get_pixel(x, y);
+        let difference =
+            a.0.iter()
+                .zip(b.0.iter())
+                .map(|(a, b)| a.abs_diff(*b))
+                .max()
+                .unwrap_or(0);
+        max_difference = max_difference.max(difference);
+        if difference > tolerance {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            // Faded copy of the expected image for context
+            let luma = (b.0[0] as u32 * 3 + b.0[1] as u32 * 6 + b.0[2] as u32) / 10;
+            let faded = (luma * b.0[3] as u32 / 255 / 4) as u8;
+            Rgba([faded, faded, faded, 255])
+        }
+    });
+
+    if mismatched == 0 {
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+    save(image, &actual_path)?;
+    save(&diff, &diff_path)?;
+    Err(SnapshotError::PixelMismatch {
+        mismatched,
+        max_difference,
+        actual_path,
+        diff_path,
+    })
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}.{suffix}.png"))
+}
+
+fn save(image: &RgbaImage, path: &Path) -> Result<(), SnapshotError> {
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|err| SnapshotError::Io(path.to_path_buf(), err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_snapshot_fails() {
+        if std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("haven-golden-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("missing.png");
+        let image = RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255]));
+        let result = compare_snapshot(&image, &path, 0);
+        assert!(matches!(result, Err(SnapshotError::Missing { .. })));
+        assert!(!path.exists());
+        assert!(sibling(&path, "actual").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -93,8 +93,8 @@ impl Image {
 }
 
 impl Image {
-    pub(crate) fn draw(&mut self, area: Area, scene: &mut Scene, app: &mut AppState) {
-        let cache_key = if let Some(ref image_id) = self.image_id {
+    pub(crate) fn cache_key(&self) -> u64 {
+        if let Some(ref image_id) = self.image_id {
             use std::collections::hash_map::DefaultHasher;
             use std::hash::{Hash, Hasher};
             let mut hasher = DefaultHasher::new();
@@ -103,7 +103,45 @@ impl Image {
             hasher.finish()
         } else {
             self.id
-        };
+        }
+    }
+
+    /// Returns the transform that places an image of `width` x `height` pixels into `area`,
+    /// along with the uniform scale applied when the aspect ratio is locked.
+    pub(crate) fn transform(
+        &self,
+        area: Area,
+        width: f64,
+        height: f64,
+        scale_factor: f64,
+    ) -> (Affine, f64) {
+        let area_x = area.x as f64 * scale_factor;
+        let area_y = area.y as f64 * scale_factor;
+        let area_width = area.width as f64 * scale_factor;
+        let area_height = area.height as f64 * scale_factor;
+
+        if self.unlocked_aspect_ratio {
+            (
+                Affine::IDENTITY
+                    .then_scale_non_uniform(area_width / width, area_height / height)
+                    .then_translate(Vec2::new(area_x, area_y)),
+                1.,
+            )
+        } else {
+            let scale = (area_width / width).min(area_height / height);
+            let dx = area_x + (area_width - width * scale) / 2.0;
+            let dy = area_y + (area_height - height * scale) / 2.0;
+            (
+                Affine::IDENTITY
+                    .then_scale(scale)
+                    .then_translate(Vec2::new(dx, dy)),
+                scale,
+            )
+        }
+    }
+
+    pub(crate) fn draw(&mut self, area: Area, scene: &mut Scene, app: &mut AppState) {
+        let cache_key = self.cache_key();
 
         if !app.image_scenes.contains_key(&cache_key) {
             let peniko_image = match self.load_image() {
@@ -130,24 +168,8 @@ impl Image {
         if let Some((image_scene, width, height)) = image_scenes.get(&cache_key) {
             let width = *width as f64;
             let height = *height as f64;
-            let area_x = area.x as f64 * app.app_context.scale_factor;
-            let area_y = area.y as f64 * app.app_context.scale_factor;
-            let area_width = area.width as f64 * app.app_context.scale_factor;
-            let area_height = area.height as f64 * app.app_context.scale_factor;
-            let mut scale = 1.;
-
-            let transform = if self.unlocked_aspect_ratio {
-                Affine::IDENTITY
-                    .then_scale_non_uniform(area_width / width, area_height / height)
-                    .then_translate(Vec2::new(area_x, area_y))
-            } else {
-                scale = (area_width / width).min(area_height / height);
-                let dx = area_x + (area_width - width * scale) / 2.0;
-                let dy = area_y + (area_height - height * scale) / 2.0;
-                Affine::IDENTITY
-                    .then_scale(scale)
-                    .then_translate(Vec2::new(dx, dy))
-            };
+            let (transform, scale) =
+                self.transform(area, width, height, app.app_context.scale_factor);
 
             scene.push_layer(
                 Fill::NonZero,
@@ -165,7 +187,7 @@ impl Image {
         }
     }
 
-    fn load_image(&self) -> Result<peniko::ImageData, Box<dyn std::error::Error>> {
+    pub(crate) fn load_image(&self) -> Result<peniko::ImageData, Box<dyn std::error::Error>> {
         #[derive(Debug)]
         pub enum ImageError {
             InvalidBuffer(String),
@@ -0,0 +1,981 @@
+use crate::app::{AppState, EditState};
+use crate::drag_drop::DragSession;
+use crate::gestures::{
+    ClickCounter, ClickLocation, FileDragState, Interaction, InteractionType, ScrollDelta,
+    ScrollUnit,
+};
+use crate::timers::GestureTimer;
+use crate::touch::TouchState;
+use crate::{
+    ClickState, DragState, GestureHandler, GesturePhase, GestureState, Insets, Key, KeyEvent,
+    Point, ZoomEvent,
+};
+use crate::{area_contains, area_contains_slop, clips_contain, hit_test};
+use backer::Area;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+use vello_svg::vello::kurbo::{Affine, BezPath};
+use winit::event::{MouseButton, MouseScrollDelta, TouchPhase};
+use winit::keyboard::NamedKey;
+use winit::window::CursorIcon;
+
+/// How far one notch of a mouse wheel scrolls, in logical pixels.
+const LINE_SCROLL_DISTANCE: f32 = 10.;
+/// Ctrl+wheel zoom per logical pixel scrolled, as an exponent so zooming in and out cancel.
+const ZOOM_PER_PIXEL: f64 = 0.01;
+
+/// The interaction state of a single window.
+///
+/// Each method delivers one input event to the gesture handlers collected from the
+/// last laid-out frame and returns whether the window needs to be redrawn.
+pub(crate) struct InputState<State> {
+    pub(crate) gesture_handlers: Vec<(u64, Area, GestureHandler<State, AppState>, Placement)>,
+    pub(crate) cursor_position: Option<Point>,
+    pub(crate) gesture_state: GestureState,
+    pub(crate) click_counter: ClickCounter,
+    /// The focus last delivered to `on_focus` handlers.
+    pub(crate) focused: Option<u64>,
+    pub(crate) touch: TouchState,
+    pub(crate) file_drag: Option<FileDrag>,
+    pub(crate) drag_session: Option<DragSession<State>>,
+    /// The cursor icon for the current pointer position.
+    pub(crate) cursor: CursorIcon,
+    pub(crate) timers: Vec<GestureTimer>,
+    /// Hover intents that fired and haven't been ended by the pointer leaving, with their delay.
+    pub(crate) hover_intents: Vec<(u64, Duration)>,
+    /// Whether a long press fired during the current press, so its release isn't a click.
+    pub(crate) long_pressed: bool,
+}
+
+/// Where a view was drawn: the clip paths enclosing it, outermost first and in window
+/// coordinates, and the transform from its layout coordinates to the window.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Placement {
+    pub(crate) clips: Rc<[BezPath]>,
+    pub(crate) transform: Affine,
+}
+
+impl Placement {
+    /// A window position in the layout coordinates of the view.
+    pub(crate) fn unproject(&self, point: Point) -> Point {
+        self.transform.inverse() * point
+    }
+
+    /// A window position relative to the top left corner of `area`.
+    pub(crate) fn local(&self, area: &Area, point: Point) -> Point {
+        let point = self.unproject(point);
+        Point::new(point.x - area.x as f64, point.y - area.y as f64)
+    }
+}
+
+/// Files being dragged over the window from the OS.
+#[derive(Debug, Default)]
+pub(crate) struct FileDrag {
+    paths: Vec<PathBuf>,
+    /// The view last sent `FileDragState::Hovered`.
+    target: Option<u64>,
+}
+
+impl<State> Default for InputState<State> {
+    fn default() -> Self {
+        Self {
+            gesture_handlers: Vec::new(),
+            cursor_position: None,
+            gesture_state: GestureState::None,
+            click_counter: ClickCounter::default(),
+            focused: None,
+            touch: TouchState::default(),
+            file_drag: None,
+            drag_session: None,
+            cursor: CursorIcon::Default,
+            timers: Vec::new(),
+            hover_intents: Vec::new(),
+            long_pressed: false,
+        }
+    }
+}
+
+impl<State: 'static> InputState<State> {
+    pub(crate) fn key_input(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        event: KeyEvent,
+    ) -> bool {
+        if event.is_pressed()
+            && event.key == Key::Named(NamedKey::Tab)
+            && self.move_focus(app_state)
+        {
+            self.sync_focus(state, app_state);
+            return true;
+        }
+        if event.is_pressed()
+            && !app_state
+                .app_context
+                .editor
+                .as_ref()
+                .is_some_and(|edit_state| edit_state.editor.consumes(&event))
+            && let Some(shortcut) = app_state
+                .shortcuts
+                .iter()
+                .find(|shortcut| shortcut.accelerator.matches(&event))
+                .cloned()
+        {
+            (shortcut.handler)(state, app_state);
+            self.sync_focus(state, app_state);
+            return true;
+        }
+        let mut needs_redraw = false;
+        let focused = app_state.focused;
+        for (id, _area, handler, _) in self.gesture_handlers.clone() {
+            if let Some(ref interaction_handler) = handler.interaction_handler
+                && (handler.interaction_type.global_key
+                    || (handler.interaction_type.key && focused == Some(id)))
+            {
+                needs_redraw = true;
+                interaction_handler(state, app_state, Interaction::Key(event.clone()));
+            }
+        }
+        needs_redraw |= self.sync_focus(state, app_state);
+        needs_redraw
+    }
+
+    /// Moves focus to the next focusable view in layout order, or the previous one with Shift held.
+    fn move_focus(&self, app_state: &mut AppState) -> bool {
+        let mut stops: Vec<u64> = Vec::new();
+        for (id, _, handler, _) in &self.gesture_handlers {
+            if handler.interaction_type.takes_focus() && !stops.contains(id) {
+                stops.push(*id);
+            }
+        }
+        if stops.is_empty() {
+            return false;
+        }
+        let backwards = app_state
+            .modifiers
+            .is_some_and(|modifiers| modifiers.state().shift_key());
+        let current = app_state
+            .focused
+            .and_then(|focused| stops.iter().position(|id| *id == focused));
+        let next = match current {
+            Some(i) if backwards => (i + stops.len() - 1) % stops.len(),
+            Some(i) => (i + 1) % stops.len(),
+            None if backwards => stops.len() - 1,
+            None => 0,
+        };
+        app_state.focused = Some(stops[next]);
+        true
+    }
+
+    /// Delivers focus changes made since the last call to `on_focus` handlers.
+    pub(crate) fn sync_focus(&mut self, state: &mut State, app_state: &mut AppState) -> bool {
+        if self.focused == app_state.focused {
+            return false;
+        }
+        let previous = std::mem::replace(&mut self.focused, app_state.focused);
+        let current = self.focused;
+        for (id, _, handler, _) in self.gesture_handlers.clone() {
+            if handler.interaction_type.focus
+                && let Some(ref on_focus) = handler.interaction_handler
+            {
+                if previous == Some(id) {
+                    on_focus(state, app_state, Interaction::Focus(false));
+                } else if current == Some(id) {
+                    on_focus(state, app_state, Interaction::Focus(true));
+                }
+            }
+        }
+        true
+    }
+
+    pub(crate) fn mouse_exited(&mut self, state: &mut State, app_state: &mut AppState) -> bool {
+        self.cursor_position = None;
+        self.cursor = CursorIcon::Default;
+        let mut needs_redraw = false;
+        for (_, _, gh, _) in self.gesture_handlers.clone() {
+            if gh.interaction_type.hover
+                && let Some(ref on_hover) = gh.interaction_handler
+            {
+                needs_redraw = true;
+                on_hover(state, app_state, Interaction::Hover(false));
+            }
+        }
+        needs_redraw |= self.update_timers(state, app_state, None, &[]);
+        needs_redraw
+    }
+
+    /// `pos` is in logical coordinates.
+    pub(crate) fn mouse_moved(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        pos: Point,
+    ) -> bool {
+        let mut needs_redraw = false;
+        self.cursor_position = Some(pos);
+        app_state.cursor_position = Some(pos);
+        if let Some(EditState { id, editor, .. }) = app_state.app_context.editor.as_mut()
+            && let Some((area, placement)) = app_state.app_context.editor_areas.get(id)
+        {
+            needs_redraw = true;
+            editor.mouse_moved(
+                placement.local(area, pos),
+                &mut app_state.app_context.layout_cx,
+                &mut app_state.app_context.font_cx,
+            );
+        }
+        let hovered = self.hovered_views(pos);
+        self.cursor = self.cursor_at(pos);
+        self.gesture_handlers
+            .clone()
+            .iter()
+            .for_each(|(id, _, gh, _)| {
+                if gh.interaction_type.hover
+                    && let Some(ref on_hover) = gh.interaction_handler
+                {
+                    needs_redraw = true;
+                    (on_hover)(state, app_state, Interaction::Hover(hovered.contains(id)));
+                }
+            });
+        needs_redraw |= self.update_timers(state, app_state, Some(pos), &hovered);
+        if self.file_drag.is_some() {
+            needs_redraw |= self.update_file_hover(state, app_state);
+        }
+        needs_redraw |= self.drag_moved(state, app_state, pos);
+        if let GestureState::Dragging {
+            start,
+            last_position,
+            capturer,
+            button: MouseButton::Left,
+        } = self.gesture_state
+        {
+            let distance = start.distance(pos);
+            self.gesture_handlers
+                .clone()
+                .iter()
+                .filter(|(id, _, gh, _)| *id == capturer && gh.interaction_type.drag)
+                .for_each(|(_, area, gh, placement)| {
+                    needs_redraw = true;
+                    if let Some(handler) = &gh.interaction_handler {
+                        let current = placement.local(area, pos);
+                        (handler)(
+                            state,
+                            app_state,
+                            Interaction::Drag(DragState::Updated {
+                                start: placement.local(area, start),
+                                current,
+                                start_global: start,
+                                current_global: pos,
+                                delta: (current - placement.local(area, last_position)).to_point(),
+                                distance: distance as f32,
+                            }),
+                        );
+                    }
+                });
+            self.gesture_state = GestureState::Dragging {
+                start,
+                last_position: pos,
+                capturer,
+                button: MouseButton::Left,
+            };
+        }
+        needs_redraw
+    }
+
+    pub(crate) fn mouse_pressed(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        button: MouseButton,
+    ) -> bool {
+        let mut needs_redraw = false;
+        if let GestureState::Dragging { button: held, .. } = self.gesture_state
+            && held != button
+        {
+            return needs_redraw;
+        }
+        if let Some(point) = self.cursor_position {
+            let click_count = self
+                .click_counter
+                .press(point, button, app_state.clock.now());
+            if button == MouseButton::Left {
+                for (id, area, handler, placement) in self
+                    .gesture_handlers
+                    .clone()
+                    .iter()
+                    .rev()
+                    .filter(|(_, _, handler, _)| handler.interaction_type.click_outside)
+                {
+                    let slop = self.hit_slop(*id, app_state);
+                    if !area_contains_slop(area, slop, placement.unproject(point))
+                        && let Some(ref on_click_outside) = handler.interaction_handler
+                    {
+                        on_click_outside(
+                            state,
+                            app_state,
+                            Interaction::ClickOutside(
+                                ClickState::Started,
+                                ClickLocation::new(
+                                    point,
+                                    placement.local(area, point),
+                                    button,
+                                    click_count,
+                                ),
+                            ),
+                        );
+                    }
+                }
+            }
+            let handlers = self.gesture_handlers.clone();
+            let captured = handlers
+                .iter()
+                .rev()
+                .find(|(_, area, handler, placement)| {
+                    hit_test(area, placement, point)
+                        && handler.interaction_type.handles_press(button)
+                })
+                .or(handlers
+                    .iter()
+                    .rev()
+                    .find(|(id, area, handler, placement)| {
+                        area_contains_slop(
+                            area,
+                            self.hit_slop(*id, app_state),
+                            placement.unproject(point),
+                        ) && clips_contain(&placement.clips, point)
+                            && handler.interaction_type.handles_press(button)
+                    }));
+            if button == MouseButton::Left {
+                let target = captured.map(|(id, _, _, _)| *id).or_else(|| {
+                    handlers
+                        .iter()
+                        .rev()
+                        .find(|(_, area, handler, placement)| {
+                            hit_test(area, placement, point)
+                                && handler.interaction_type.takes_focus()
+                        })
+                        .map(|(id, _, _, _)| *id)
+                });
+                app_state.focused = target.filter(|target| {
+                    handlers.iter().any(|(id, _, handler, _)| {
+                        id == target && handler.interaction_type.takes_focus()
+                    })
+                });
+            }
+            if let Some((capturer, _, _, _)) = captured {
+                needs_redraw = true;
+                // A view's handlers are separate entries, so a click handler still sees
+                // the press when a drag source or drag handler captured it
+                for (_, area, handler, placement) in
+                    handlers.iter().filter(|(id, _, _, _)| id == capturer)
+                {
+                    if handler.interaction_type.clicks(button)
+                        && let Some(ref on_click) = handler.interaction_handler
+                    {
+                        on_click(
+                            state,
+                            app_state,
+                            Interaction::Click(
+                                ClickState::Started,
+                                ClickLocation::new(
+                                    point,
+                                    placement.local(area, point),
+                                    button,
+                                    click_count,
+                                ),
+                            ),
+                        );
+                    } else if button == MouseButton::Left
+                        && handler.interaction_type.drag
+                        && let Some(ref on_drag) = handler.interaction_handler
+                    {
+                        on_drag(
+                            state,
+                            app_state,
+                            Interaction::Drag(DragState::Began {
+                                start: placement.local(area, point),
+                                start_global: point,
+                            }),
+                        );
+                    }
+                }
+                self.gesture_state = GestureState::Dragging {
+                    start: point,
+                    last_position: point,
+                    capturer: *capturer,
+                    button,
+                };
+                if button == MouseButton::Left {
+                    self.schedule_long_press(*capturer, point, app_state.clock.now());
+                }
+            }
+            let editor_slop = app_state
+                .app_context
+                .editor
+                .as_ref()
+                .map(|edit_state| self.hit_slop(edit_state.id, app_state))
+                .unwrap_or_default();
+            if button == MouseButton::Left
+                && let Some(EditState { id, editor, .. }) = app_state.app_context.editor.as_mut()
+                && let Some((area, placement)) = app_state.app_context.editor_areas.get(id)
+                && area_contains_slop(area, editor_slop, placement.unproject(point))
+            {
+                editor.mouse_pressed(
+                    &mut app_state.app_context.layout_cx,
+                    &mut app_state.app_context.font_cx,
+                    click_count,
+                );
+            }
+        }
+        needs_redraw |= self.sync_focus(state, app_state);
+        needs_redraw
+    }
+
+    pub(crate) fn mouse_released(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        button: MouseButton,
+    ) -> bool {
+        let mut needs_redraw = false;
+        let gesture_state = self.gesture_state;
+        if let GestureState::Dragging { button: held, .. } = gesture_state
+            && held != button
+        {
+            return needs_redraw;
+        }
+        if let Some(current) = self.cursor_position {
+            let click_count = self.click_counter.count;
+            if button == MouseButton::Left
+                && let Some(EditState { id, editor, .. }) = app_state.app_context.editor.as_mut()
+                && let Some((area, placement)) = app_state.app_context.editor_areas.get(id)
+            {
+                editor.mouse_released();
+                needs_redraw = true;
+                if !area_contains(area, placement.unproject(current))
+                    && (!matches!(gesture_state, GestureState::Dragging { .. })
+                        || match gesture_state {
+                            GestureState::Dragging { capturer, .. } => capturer != *id,
+                            _ => false,
+                        })
+                {
+                    app_state.end_editing();
+                }
+            }
+            // A drag that picked up a payload ends in a drop rather than a click, and a
+            // press held long enough already fired its long press
+            let dropped = self.end_drag(state, app_state, current);
+            let long_pressed = self.long_pressed;
+            needs_redraw |= dropped;
+            if let GestureState::Dragging {
+                start,
+                last_position,
+                capturer,
+                ..
+            } = gesture_state
+            {
+                let distance = start.distance(current);
+                self.gesture_handlers
+                    .clone()
+                    .iter()
+                    .filter(|(id, _, _, _)| *id == capturer)
+                    .for_each(|(_, area, gh, placement)| {
+                        if let (Some(on_click), true) =
+                            (&gh.interaction_handler, gh.interaction_type.clicks(button))
+                        {
+                            needs_redraw = true;
+                            if !dropped && !long_pressed && hit_test(area, placement, current) {
+                                on_click(
+                                    state,
+                                    app_state,
+                                    Interaction::Click(
+                                        ClickState::Completed,
+                                        ClickLocation::new(
+                                            current,
+                                            placement.local(area, current),
+                                            button,
+                                            click_count,
+                                        ),
+                                    ),
+                                );
+                            } else {
+                                on_click(
+                                    state,
+                                    app_state,
+                                    Interaction::Click(
+                                        ClickState::Cancelled,
+                                        ClickLocation::new(
+                                            current,
+                                            placement.local(area, current),
+                                            button,
+                                            click_count,
+                                        ),
+                                    ),
+                                );
+                            }
+                        }
+                        if let (Some(on_drag), true) = (
+                            &gh.interaction_handler,
+                            button == MouseButton::Left && gh.interaction_type.drag,
+                        ) {
+                            needs_redraw = true;
+                            on_drag(
+                                state,
+                                app_state,
+                                Interaction::Drag(DragState::Completed {
+                                    start: placement.local(area, start),
+                                    current: placement.local(area, current),
+                                    start_global: start,
+                                    current_global: current,
+                                    delta: (placement.local(area, current)
+                                        - placement.local(area, last_position))
+                                    .to_point(),
+                                    distance: distance as f32,
+                                }),
+                            );
+                        }
+                    });
+            }
+            let press_start = match gesture_state {
+                GestureState::Dragging { start, .. } => Some(start),
+                _ => None,
+            };
+            if button == MouseButton::Left {
+                for (id, area, handler, placement) in self
+                    .gesture_handlers
+                    .clone()
+                    .iter()
+                    .filter(|(_, _, h, _)| h.interaction_type.click_outside)
+                {
+                    let slop = self.hit_slop(*id, app_state);
+                    let outside =
+                        |p: Point| !area_contains_slop(area, slop, placement.unproject(p));
+                    if outside(current)
+                        && press_start.is_some_and(outside)
+                        && let Some(ref handler) = handler.interaction_handler
+                    {
+                        needs_redraw = true;
+                        handler(
+                            state,
+                            app_state,
+                            Interaction::ClickOutside(
+                                ClickState::Completed,
+                                ClickLocation::new(
+                                    current,
+                                    placement.local(area, current),
+                                    button,
+                                    click_count,
+                                ),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+        self.gesture_state = GestureState::None;
+        self.long_pressed = false;
+        self.cancel_long_press();
+        if let Some(current) = self.cursor_position {
+            self.cursor = self.cursor_at(current);
+        }
+        needs_redraw |= self.sync_focus(state, app_state);
+        needs_redraw
+    }
+
+    pub(crate) fn scrolled(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+    ) -> bool {
+        let Some(current) = self.cursor_position else {
+            return false;
+        };
+        let delta = match delta {
+            MouseScrollDelta::LineDelta(x, y) => ScrollDelta {
+                x: x * LINE_SCROLL_DISTANCE,
+                y: y * LINE_SCROLL_DISTANCE,
+                phase,
+                unit: ScrollUnit::Line,
+            },
+            MouseScrollDelta::PixelDelta(physical_position) => ScrollDelta {
+                x: physical_position.x as f32,
+                y: physical_position.y as f32,
+                phase,
+                unit: ScrollUnit::Pixel,
+            },
+        };
+        // Ctrl+wheel zooms views that handle zooming instead of scrolling them
+        if app_state
+            .modifiers
+            .is_some_and(|modifiers| modifiers.state().control_key())
+        {
+            let routed = self.route(current, |interaction_type| interaction_type.zoom);
+            if !routed.is_empty() {
+                let zoom = (delta.y as f64 * ZOOM_PER_PIXEL).exp();
+                return self.propagate(state, app_state, routed, |area, placement| {
+                    Interaction::Zoom(ZoomEvent {
+                        phase: GesturePhase::Changed,
+                        delta: zoom,
+                        focus: placement.local(area, current),
+                    })
+                });
+            }
+        }
+        // Shift turns a vertical wheel into horizontal scrolling. Some platforms already
+        // do this themselves, in which case the delta arrives on x
+        let delta = if app_state
+            .modifiers
+            .is_some_and(|modifiers| modifiers.state().shift_key())
+            && delta.x == 0.
+        {
+            ScrollDelta {
+                x: delta.y,
+                y: 0.,
+                ..delta
+            }
+        } else {
+            delta
+        };
+        let routed = self.route(current, |interaction_type| interaction_type.scroll);
+        self.propagate(state, app_state, routed, |_, _| Interaction::Scroll(delta))
+    }
+
+    /// Adds files to the drag in progress, starting one if needed.
+    pub(crate) fn files_hovered(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        paths: Vec<PathBuf>,
+    ) -> bool {
+        self.file_drag
+            .get_or_insert_with(FileDrag::default)
+            .paths
+            .extend(paths);
+        self.update_file_hover(state, app_state)
+    }
+
+    pub(crate) fn file_hover_cancelled(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+    ) -> bool {
+        let Some(FileDrag {
+            target: Some(target),
+            ..
+        }) = self.file_drag.take()
+        else {
+            return false;
+        };
+        self.send_file_hover(state, app_state, target, FileDragState::Exited)
+    }
+
+    /// Delivers all files of a drop to the view under the cursor that accepts drops.
+    pub(crate) fn files_dropped(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        paths: Vec<PathBuf>,
+    ) -> bool {
+        let mut needs_redraw = self.file_hover_cancelled(state, app_state);
+        // The OS doesn't report the cursor while dragging on every platform, so fall back
+        // to the last position seen in any window
+        let Some(position) = self.cursor_position.or(app_state.cursor_position) else {
+            return needs_redraw;
+        };
+        let routed = self.route(position, |interaction_type| interaction_type.file_drop);
+        needs_redraw |= self.propagate(state, app_state, routed, |_, _| {
+            Interaction::FileDrop(paths.clone(), position)
+        });
+        needs_redraw
+    }
+
+    /// Sends the drag to the topmost `on_file_hover` view under the cursor, and `Exited`
+    /// to the previous one if it changed.
+    fn update_file_hover(&mut self, state: &mut State, app_state: &mut AppState) -> bool {
+        let position = self.cursor_position.or(app_state.cursor_position);
+        let target = position.and_then(|position| {
+            self.gesture_handlers
+                .iter()
+                .rev()
+                .find(|(_, area, handler, placement)| {
+                    handler.interaction_type.file_hover && hit_test(area, placement, position)
+                })
+                .map(|(id, _, _, _)| *id)
+        });
+        let Some(drag) = self.file_drag.as_mut() else {
+            return false;
+        };
+        let previous = std::mem::replace(&mut drag.target, target);
+        let paths = drag.paths.clone();
+        let mut needs_redraw = false;
+        if let Some(previous) = previous
+            && Some(previous) != target
+        {
+            needs_redraw |= self.send_file_hover(state, app_state, previous, FileDragState::Exited);
+        }
+        if let (Some(target), Some(position)) = (target, position) {
+            needs_redraw |= self.send_file_hover(
+                state,
+                app_state,
+                target,
+                FileDragState::Hovered { paths, position },
+            );
+        }
+        needs_redraw
+    }
+
+    fn send_file_hover(
+        &self,
+        state: &mut State,
+        app_state: &mut AppState,
+        target: u64,
+        drag: FileDragState,
+    ) -> bool {
+        let mut sent = false;
+        for (id, _, handler, _) in self.gesture_handlers.clone() {
+            if id == target
+                && handler.interaction_type.file_hover
+                && let Some(ref on_file_hover) = handler.interaction_handler
+            {
+                sent = true;
+                on_file_hover(state, app_state, Interaction::FileHover(drag.clone()));
+            }
+        }
+        sent
+    }
+
+    /// Views under `point` that are hovered, topmost first. Hover stops at the first view
+    /// taking pointer input that isn't `pass_through`, so covered views aren't hovered.
+    fn hovered_views(&self, point: Point) -> Vec<u64> {
+        let mut hovered = Vec::new();
+        let mut covering = None;
+        for (id, area, handler, placement) in self.gesture_handlers.iter().rev() {
+            if covering.is_some_and(|covering| covering != *id) {
+                break;
+            }
+            if !handler.interaction_type.is_pointer_target() || !hit_test(area, placement, point) {
+                continue;
+            }
+            if (handler.interaction_type.hover || handler.interaction_type.hover_intent.is_some())
+                && !hovered.contains(id)
+            {
+                hovered.push(*id);
+            }
+            if !self.passes_through(*id) {
+                covering = Some(*id);
+            }
+        }
+        hovered
+    }
+
+    /// Handlers for a pointer event at `point`, topmost first. The event goes to the
+    /// topmost view accepting it and continues below only through `pass_through` views.
+    fn route(
+        &self,
+        point: Point,
+        accepts: impl Fn(&InteractionType) -> bool,
+    ) -> Vec<(u64, Area, GestureHandler<State, AppState>, Placement)> {
+        let mut routed = Vec::new();
+        let mut last = None;
+        for (id, area, handler, placement) in self.gesture_handlers.iter().rev() {
+            if last.is_some_and(|last| last != *id) {
+                break;
+            }
+            if accepts(&handler.interaction_type) && hit_test(area, placement, point) {
+                routed.push((*id, *area, handler.clone(), placement.clone()));
+                if !self.passes_through(*id) {
+                    last = Some(*id);
+                }
+            }
+        }
+        routed
+    }
+
+    /// Delivers an event to routed handlers until one calls `AppState::stop_propagation`.
+    fn propagate(
+        &self,
+        state: &mut State,
+        app_state: &mut AppState,
+        routed: Vec<(u64, Area, GestureHandler<State, AppState>, Placement)>,
+        interaction: impl Fn(&Area, &Placement) -> Interaction,
+    ) -> bool {
+        let mut delivered = false;
+        app_state.propagation_stopped = false;
+        for (_, area, handler, placement) in routed {
+            if let Some(ref interaction_handler) = handler.interaction_handler {
+                delivered = true;
+                interaction_handler(state, app_state, interaction(&area, &placement));
+                if app_state.propagation_stopped {
+                    break;
+                }
+            }
+        }
+        app_state.propagation_stopped = false;
+        delivered
+    }
+
+    fn passes_through(&self, id: u64) -> bool {
+        self.gesture_handlers
+            .iter()
+            .any(|(other, _, handler, _)| *other == id && handler.interaction_type.pass_through)
+    }
+
+    /// The cursor of the view being dragged, or of the topmost view under `point` that sets
+    /// one. Views taking pointer input without a cursor of their own cover those below.
+    fn cursor_at(&self, point: Point) -> CursorIcon {
+        let view_cursor = |view: u64| {
+            self.gesture_handlers
+                .iter()
+                .find_map(|(id, _, handler, _)| {
+                    (*id == view)
+                        .then_some(handler.interaction_type.cursor)
+                        .flatten()
+                })
+        };
+        if let GestureState::Dragging { capturer, .. } = self.gesture_state
+            && let Some(cursor) = view_cursor(capturer)
+        {
+            return cursor;
+        }
+        for (id, area, handler, placement) in self.gesture_handlers.iter().rev() {
+            let interaction_type = &handler.interaction_type;
+            if !(interaction_type.is_pointer_target() || interaction_type.cursor.is_some())
+                || !hit_test(area, placement, point)
+            {
+                continue;
+            }
+            if let Some(cursor) = view_cursor(*id) {
+                return cursor;
+            }
+            if !self.passes_through(*id) {
+                break;
+            }
+        }
+        CursorIcon::Default
+    }
+
+    /// The hit slop set on the view with `id`, or the app-wide default.
+    fn hit_slop(&self, id: u64, app_state: &AppState) -> Insets {
+        self.gesture_handlers
+            .iter()
+            .find_map(|(other, _, handler, _)| {
+                (*other == id)
+                    .then_some(handler.interaction_type.hit_slop)
+                    .flatten()
+            })
+            .unwrap_or(app_state.hit_slop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use vello_svg::vello::kurbo::{Rect, Shape};
+
+    #[derive(Default)]
+    struct State {
+        focus: Vec<(usize, bool)>,
+        keys: Vec<usize>,
+        clicks: u32,
+    }
+
+    fn view<'a>(_state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        row((0..3)
+            .map(|index| {
+                rect(crate::id!(index as u64))
+                    .fill(TRANSPARENT)
+                    .view()
+                    .on_focus(move |state: &mut State, _app, focused| {
+                        state.focus.push((index, focused))
+                    })
+                    .on_key(move |state: &mut State, _app, _event| state.keys.push(index))
+                    .finish(app.ctx())
+            })
+            .collect())
+        .pad(50.)
+    }
+
+    fn focused(app: &TestApp<State>) -> Vec<usize> {
+        app.state
+            .focus
+            .iter()
+            .filter(|(_, focused)| *focused)
+            .map(|(index, _)| *index)
+            .collect()
+    }
+
+    #[test]
+    fn tab_follows_draw_order_and_wraps() {
+        let mut app = TestApp::new(State::default(), view);
+        for _ in 0..4 {
+            app.key(Key::Named(NamedKey::Tab));
+        }
+        assert_eq!(focused(&app), vec![0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn shift_tab_reverses_the_order() {
+        let mut app = TestApp::new(State::default(), view);
+        app.modifiers(ModifiersState::SHIFT);
+        for _ in 0..4 {
+            app.key(Key::Named(NamedKey::Tab));
+        }
+        assert_eq!(focused(&app), vec![2, 1, 0, 2]);
+    }
+
+    #[test]
+    fn keys_only_reach_the_focused_view() {
+        let mut app = TestApp::new(State::default(), view);
+        app.key_down(Key::Character("a".into()));
+        assert!(app.state.keys.is_empty());
+        app.key(Key::Named(NamedKey::Tab));
+        app.key(Key::Named(NamedKey::Tab));
+        app.state.keys.clear();
+        app.key_down(Key::Character("a".into()));
+        assert_eq!(app.state.keys, vec![1]);
+    }
+
+    #[test]
+    fn losing_focus_calls_on_focus_with_false() {
+        let mut app = TestApp::new(State::default(), view);
+        app.key(Key::Named(NamedKey::Tab));
+        app.key(Key::Named(NamedKey::Tab));
+        assert_eq!(app.state.focus, vec![(0, true), (0, false), (1, true)]);
+        // Clicking outside every focusable view blurs the focused one
+        app.click(10., 10.);
+        assert_eq!(app.state.focus.last(), Some(&(1, false)));
+        assert_eq!(app.app_state().focused(), None);
+    }
+
+    fn clipped_view<'a>(_state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        // Only the left half of the view is visible
+        clipping(
+            |area| {
+                Rect::new(
+                    area.x as f64,
+                    area.y as f64,
+                    (area.x + area.width / 2.) as f64,
+                    (area.y + area.height) as f64,
+                )
+                .to_path(0.1)
+            },
+            rect(crate::id!())
+                .fill(TRANSPARENT)
+                .view()
+                .on_click(|state: &mut State, _app, _click, _location| state.clicks += 1)
+                .finish(app.ctx()),
+        )
+    }
+
+    #[test]
+    fn presses_outside_the_clip_are_not_delivered() {
+        let mut app = TestApp::new(State::default(), clipped_view).size(200., 100.);
+        app.click(150., 50.);
+        assert_eq!(app.state.clicks, 0);
+        app.click(50., 50.);
+        assert!(app.state.clicks > 0);
+    }
+}
@@ -1,54 +1,77 @@
 #![allow(clippy::type_complexity, clippy::too_many_arguments)]
 
+mod animation;
 mod app;
 mod background_style;
 mod button;
 mod circle;
+mod cpu;
+mod drag_drop;
 mod draw_layout;
 mod dropdown;
 mod editor;
 mod event;
 mod gestures;
+mod golden;
 mod image;
+mod input;
 mod models;
 mod path;
 mod rect;
 mod scroller;
 mod shape;
+mod shortcut;
 mod slider;
+mod snapshot;
 mod svg;
+mod test_app;
 mod text;
 mod text_field;
+mod timers;
 mod toggle;
+mod touch;
 mod view;
 
-pub use app::{App, AppBuilder, AppCtx, AppState, Callback, RedrawTrigger, View, Window};
+pub use animation::{Animatable, Easing, Transition};
+pub use app::{
+    App, AppBuilder, AppCtx, AppState, Callback, FrameTime, RedrawTrigger, View, Window,
+};
 pub use backer::{Area, Layout, nodes::*};
 pub use background_style::BrushSource;
 pub use button::*;
 pub use bytemuck;
 pub use circle::circle;
+pub use cpu::CpuRenderer;
 pub use dropdown::*;
 pub use editor::*;
-pub use gestures::{ClickState, DragState, EditInteraction, GestureHandler, GestureState};
+pub use gestures::{
+    ClickState, DragState, EditInteraction, FileDragState, GestureHandler, GesturePhase,
+    GestureState, Pan, Pinch, Rotation, ScrollDelta, ScrollUnit, ZoomEvent,
+};
+pub use golden::{SnapshotError, UPDATE_SNAPSHOTS_VAR, compare_snapshot};
 pub use image::{ImageSource, image, image_from_bytes, image_from_path};
 pub use parley::{Alignment, FontWeight};
 pub use path::path;
 pub use rect::rect;
 pub use scroller::*;
+pub use shortcut::{Accelerator, primary_modifier};
 pub use slider::*;
+pub use snapshot::{FrameSnapshot, ViewKind, ViewNode};
 pub use svg::svg;
+pub use test_app::TestApp;
 pub use text::*;
 pub use text_field::*;
+pub use timers::TimerId;
 pub use toggle::*;
 use vello_svg::vello::peniko::color::AlphaColor;
 use vello_svg::vello::peniko::color::Srgb;
-pub use view::{clipping, const_hash, scope};
-pub use winit::keyboard::NamedKey;
-pub use winit::window::WindowId;
+pub use view::{blend, clipping, const_hash, opacity, scope, transform};
+pub use winit::event::{ElementState, MouseButton, TouchPhase};
+pub use winit::keyboard::{KeyCode, ModifiersState, NamedKey, PhysicalKey};
+pub use winit::window::{CursorIcon, WindowId};
 
-pub use vello_svg::vello::kurbo::{BezPath, Cap, Join, Point, Stroke};
-pub use vello_svg::vello::peniko::{Brush, Gradient};
+pub use vello_svg::vello::kurbo::{Affine, BezPath, Cap, Join, Point, Stroke};
+pub use vello_svg::vello::peniko::{BlendMode, Brush, Compose, Gradient, Mix};
 
 pub use models::*;
 
@@ -56,18 +79,19 @@ pub type Color = AlphaColor<Srgb>;
 
 pub(crate) fn adjust_brush(brush: &Brush, depressed: bool, hovered: bool) -> Brush {
     match brush {
-        Brush::Solid(color) => {
-            let adjusted = match (depressed, hovered) {
-                (true, _) => color.map_lightness(|l| l - 0.1),
-                (false, true) => color.map_lightness(|l| l + 0.1),
-                (false, false) => *color,
-            };
-            Brush::Solid(adjusted)
-        }
+        Brush::Solid(color) => Brush::Solid(adjust_color(*color, depressed, hovered)),
         other => other.clone(),
     }
 }
 
+pub(crate) fn adjust_color(color: Color, depressed: bool, hovered: bool) -> Color {
+    match (depressed, hovered) {
+        (true, _) => color.map_lightness(|l| l - 0.1),
+        (false, true) => color.map_lightness(|l| l + 0.1),
+        (false, false) => color,
+    }
+}
+
 const RUBIK_FONT: &[u8] = include_bytes!("../assets/Rubik-VariableFont_wght.ttf");
 const DEFAULT_FONT_FAMILY: &str = "Rubik";
 pub const DEFAULT_STROKE_WIDTH: f32 = 1.;
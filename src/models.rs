@@ -1,6 +1,8 @@
+use crate::input::Placement;
 use std::{fmt::Debug, rc::Rc};
-use vello_svg::vello::kurbo::Point;
-use winit::keyboard::{NamedKey, SmolStr};
+use vello_svg::vello::kurbo::{BezPath, Point, Shape};
+use winit::event::ElementState;
+use winit::keyboard::{ModifiersState, NamedKey, PhysicalKey, SmolStr};
 
 pub use backer::{Align, Area};
 
@@ -17,17 +19,66 @@ pub(crate) fn area_contains(area: &Area, point: Point) -> bool {
     false
 }
 
-pub(crate) fn area_contains_padded(area: &Area, point: Point, padding: f64) -> bool {
-    let x = point.x;
-    let y = point.y;
-    if x > area.x as f64 - padding
-        && y > area.y as f64 - padding
-        && y < area.y as f64 + area.height as f64 + padding
-        && x < area.x as f64 + area.width as f64 + padding
-    {
-        return true;
+/// Whether `point` is inside every clip path.
+pub(crate) fn clips_contain(clips: &[BezPath], point: Point) -> bool {
+    clips.iter().all(|clip| clip.contains(point))
+}
+
+/// Whether the window position `point` hits a view drawn at `area`, inside the clips it
+/// was drawn in.
+pub(crate) fn hit_test(area: &Area, placement: &Placement, point: Point) -> bool {
+    area_contains(area, placement.unproject(point)) && clips_contain(&placement.clips, point)
+}
+
+/// Whether `point` is inside `area` grown by `slop`.
+pub(crate) fn area_contains_slop(area: &Area, slop: Insets, point: Point) -> bool {
+    area_contains(&slop.expand(area), point)
+}
+
+/// Distances from the edges of an area, e.g. the hit slop around a view.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Insets {
+    pub top: f32,
+    pub left: f32,
+    pub bottom: f32,
+    pub right: f32,
+}
+
+impl Insets {
+    pub const ZERO: Insets = Insets::uniform(0.);
+
+    pub const fn uniform(value: f32) -> Self {
+        Insets {
+            top: value,
+            left: value,
+            bottom: value,
+            right: value,
+        }
+    }
+
+    pub const fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Insets {
+            top: vertical,
+            left: horizontal,
+            bottom: vertical,
+            right: horizontal,
+        }
+    }
+
+    pub(crate) fn expand(&self, area: &Area) -> Area {
+        Area {
+            x: area.x - self.left,
+            y: area.y - self.top,
+            width: area.width + self.left + self.right,
+            height: area.height + self.top + self.bottom,
+        }
+    }
+}
+
+impl From<f32> for Insets {
+    fn from(value: f32) -> Self {
+        Insets::uniform(value)
     }
-    false
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -47,6 +98,25 @@ impl Key {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// The key with the keyboard layout applied.
+    pub key: Key,
+    /// The position of the key on the keyboard, independent of layout.
+    pub physical_key: PhysicalKey,
+    pub state: ElementState,
+    /// Whether this press was generated by holding the key down.
+    pub is_repeat: bool,
+    /// The modifiers held when the event occurred.
+    pub modifiers: ModifiersState,
+}
+
+impl KeyEvent {
+    pub fn is_pressed(&self) -> bool {
+        self.state == ElementState::Pressed
+    }
+}
+
 type Getter<State, T> = Rc<dyn Fn(&State) -> T>;
 type Setter<State, T> = Rc<dyn Fn(&mut State, T)>;
 
@@ -104,3 +174,44 @@ impl<State, T> Binding<State, T> {
         (self.set)(state, temp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insets_expand_each_edge() {
+        let area = Area {
+            x: 10.,
+            y: 20.,
+            width: 30.,
+            height: 40.,
+        };
+        let insets = Insets {
+            top: 1.,
+            left: 2.,
+            bottom: 3.,
+            right: 4.,
+        };
+        assert_eq!(
+            insets.expand(&area),
+            Area {
+                x: 8.,
+                y: 19.,
+                width: 36.,
+                height: 44.,
+            }
+        );
+        assert_eq!(Insets::ZERO.expand(&area), area);
+        assert!(area_contains_slop(
+            &area,
+            Insets::uniform(5.),
+            Point::new(6., 18.)
+        ));
+        assert!(!area_contains_slop(
+            &area,
+            Insets::symmetric(5., 0.),
+            Point::new(6., 18.)
+        ));
+    }
+}
@@ -1,8 +1,9 @@
 use std::rc::Rc;
 
+use crate::Color;
 use crate::app::{AppCtx, View};
 use crate::background_style::BrushSource;
-use crate::shape::PathData;
+use crate::shape::{PathData, Shadow, ShadowShape};
 use crate::view::{Drawable, DrawableType};
 use backer::{Area, Layout};
 use vello_svg::vello::kurbo::{BezPath, Stroke};
@@ -12,6 +13,8 @@ pub struct Path {
     builder: Rc<dyn Fn(Area) -> BezPath>,
     fill: Option<BrushSource<()>>,
     stroke: Option<(BrushSource<()>, Stroke)>,
+    shadows: Vec<Shadow>,
+    backdrop_blur: Option<f32>,
 }
 
 pub fn path(id: u64, builder: impl Fn(Area) -> BezPath + 'static) -> Path {
@@ -20,6 +23,8 @@ pub fn path(id: u64, builder: impl Fn(Area) -> BezPath + 'static) -> Path {
         builder: Rc::new(builder),
         fill: None,
         stroke: None,
+        shadows: Vec::new(),
+        backdrop_blur: None,
     }
 }
 
@@ -32,6 +37,47 @@ impl Path {
         self.stroke = Some((brush.into(), style));
         self
     }
+    /// Casts a shadow behind the shape, moved by `offset`. `blur_radius` softens its edge
+    /// and `spread` grows it.
+    pub fn shadow(
+        mut self,
+        color: Color,
+        offset: (f32, f32),
+        blur_radius: f32,
+        spread: f32,
+    ) -> Self {
+        self.shadows.push(Shadow {
+            color,
+            offset,
+            blur_radius,
+            spread,
+            inset: false,
+        });
+        self
+    }
+    /// Blurs whatever is drawn behind the shape, for frosted panels. Pair it with a
+    /// translucent fill.
+    pub fn backdrop_blur(mut self, radius: f32) -> Self {
+        self.backdrop_blur = Some(radius);
+        self
+    }
+    /// Shades the inside of the shape along its edges, as if it were cut into the surface.
+    pub fn inner_shadow(
+        mut self,
+        color: Color,
+        offset: (f32, f32),
+        blur_radius: f32,
+        spread: f32,
+    ) -> Self {
+        self.shadows.push(Shadow {
+            color,
+            offset,
+            blur_radius,
+            spread,
+            inset: true,
+        });
+        self
+    }
     pub fn view<State>(self) -> Drawable<State> {
         Drawable {
             view_type: DrawableType::Path(Box::new(PathData {
@@ -39,6 +85,9 @@ impl Path {
                 builder: self.builder,
                 fill: self.fill,
                 stroke: self.stroke,
+                shadows: self.shadows,
+                backdrop_blur: self.backdrop_blur,
+                shadow_shape: ShadowShape::Path,
             })),
             gesture_handlers: Vec::new(),
         }
@@ -1,8 +1,8 @@
-use crate::DEFAULT_CORNER_ROUNDING;
 use crate::app::{AppCtx, View};
 use crate::background_style::BrushSource;
-use crate::shape::{PathData, rect_path};
+use crate::shape::{PathData, Shadow, ShadowShape, rect_path};
 use crate::view::{Drawable, DrawableType};
+use crate::{Color, DEFAULT_CORNER_ROUNDING};
 use backer::Layout;
 use vello_svg::vello::kurbo::Stroke;
 use vello_svg::vello::peniko::Brush;
@@ -13,6 +13,8 @@ pub struct Rect {
     fill: Option<BrushSource<()>>,
     stroke: Option<(BrushSource<()>, Stroke)>,
     corner_rounding: (f32, f32, f32, f32),
+    shadows: Vec<Shadow>,
+    backdrop_blur: Option<f32>,
 }
 
 pub fn rect(id: u64) -> Rect {
@@ -26,6 +28,8 @@ pub fn rect(id: u64) -> Rect {
             DEFAULT_CORNER_ROUNDING,
             DEFAULT_CORNER_ROUNDING,
         ),
+        shadows: Vec::new(),
+        backdrop_blur: None,
     }
 }
 
@@ -52,12 +56,65 @@ impl Rect {
         self.stroke = Some((brush.into(), style.into()));
         self
     }
+    /// Casts a shadow behind the shape, moved by `offset`. `blur_radius` softens its edge
+    /// and `spread` grows it.
+    pub fn shadow(
+        mut self,
+        color: Color,
+        offset: (f32, f32),
+        blur_radius: f32,
+        spread: f32,
+    ) -> Self {
+        self.shadows.push(Shadow {
+            color,
+            offset,
+            blur_radius,
+            spread,
+            inset: false,
+        });
+        self
+    }
+    /// Blurs whatever is drawn behind the shape, for frosted panels. Pair it with a
+    /// translucent fill.
+    pub fn backdrop_blur(mut self, radius: f32) -> Self {
+        self.backdrop_blur = Some(radius);
+        self
+    }
+    /// Shades the inside of the shape along its edges, as if it were cut into the surface.
+    pub fn inner_shadow(
+        mut self,
+        color: Color,
+        offset: (f32, f32),
+        blur_radius: f32,
+        spread: f32,
+    ) -> Self {
+        self.shadows.push(Shadow {
+            color,
+            offset,
+            blur_radius,
+            spread,
+            inset: true,
+        });
+        self
+    }
     pub(crate) fn into_path_data(self) -> PathData {
+        let (top_left, top_right, bottom_right, bottom_left) = self.corner_rounding;
+        let shadow_shape = if [top_right, bottom_right, bottom_left]
+            .iter()
+            .all(|r| *r == top_left)
+        {
+            ShadowShape::RoundedRect(top_left)
+        } else {
+            ShadowShape::Path
+        };
         PathData {
             id: self.id,
             builder: rect_path(self.corner_rounding),
             fill: self.fill,
             stroke: self.stroke,
+            shadows: self.shadows,
+            backdrop_blur: self.backdrop_blur,
+            shadow_shape,
         }
     }
     pub fn view<State>(self) -> Drawable<State> {
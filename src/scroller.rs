@@ -1,15 +1,33 @@
 use crate::{
-    DEFAULT_CORNER_ROUNDING, TRANSPARENT,
+    DEFAULT_CORNER_ROUNDING, ScrollDelta, TRANSPARENT,
     app::{AppCtx, AppState, View},
     rect,
     view::clipping,
 };
 use backer::{
     Area, Layout,
-    nodes::{column, draw, empty, stack},
+    nodes::{column, draw, empty, row, stack},
+};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
 };
-use std::{cell::RefCell, rc::Rc};
 use vello_svg::vello::kurbo::{RoundedRect, Shape as _};
+use winit::event::TouchPhase;
+
+/// Slowest fling, in logical pixels per second, that keeps the content moving.
+const MIN_FLING_VELOCITY: f32 = 50.;
+/// Velocity kept per millisecond of a fling.
+const FLING_DECELERATION: f32 = 0.998;
+/// Velocity kept per millisecond of a fling that has run past the end of the content.
+const OVERSCROLL_DECELERATION: f32 = 0.98;
+/// Overscroll kept per millisecond while springing back.
+const SPRING_BACK: f32 = 0.985;
+/// A lift this long after the last movement doesn't fling.
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+/// Longest frame step, so a stalled frame doesn't jump the content.
+const MAX_FRAME_STEP: f32 = 0.1;
 
 #[derive(Debug, Clone, Default)]
 pub struct ScrollerState {
@@ -18,15 +36,56 @@ pub struct ScrollerState {
     compensated: f32,
     offset: f32,
     area: Area,
+    /// Logical pixels per second, sampled during a trackpad gesture and decaying while flinging.
+    velocity: f32,
+    /// Fingers are on the trackpad.
+    tracking: bool,
+    /// The fingers were lifted since the last frame.
+    released: bool,
+    flinging: bool,
+    /// How far the content has been pulled past either end.
+    overscroll: f32,
+    last_frame: Option<Instant>,
+    last_sample: Option<Instant>,
+    axis: ScrollAxis,
+}
+
+/// The direction a `scroller` lays out and scrolls its cells in.
+///
+/// A scroller only moves along one axis. Scrolling both ways at once is out of scope; put
+/// horizontal scrollers in the cells of a vertical one instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScrollAxis {
+    /// Cells in a column, measured by their minimum height.
+    #[default]
+    Vertical,
+    /// Cells in a row, measured by their minimum width.
+    Horizontal,
+}
+
+impl ScrollAxis {
+    fn extent(self, area: Area) -> f32 {
+        match self {
+            ScrollAxis::Vertical => area.height,
+            ScrollAxis::Horizontal => area.width,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 struct Element {
-    height: f32,
+    /// Length along the scroll axis.
+    size: f32,
     index: usize,
 }
 
 impl ScrollerState {
+    /// Scrolls horizontally with `ScrollAxis::Horizontal`. Vertical by default.
+    pub fn axis(mut self, axis: ScrollAxis) -> Self {
+        self.axis = axis;
+        self
+    }
+
     fn fill_forwards<'a, State>(
         &mut self,
         ctx: &mut AppCtx,
@@ -34,14 +93,16 @@ impl ScrollerState {
         id: u64,
         cell: &dyn Fn(usize, u64, &mut AppCtx) -> Option<Layout<'a, View<State>, AppCtx>>,
     ) {
-        let mut current_height = self.visible_window.iter().fold(0., |acc, e| acc + e.height);
+        let axis = self.axis;
+        let mut current_size = self.visible_window.iter().fold(0., |acc, e| acc + e.size);
         let mut index = self.visible_window.last().map(|l| l.index).unwrap_or(0)
             + if self.visible_window.is_empty() { 0 } else { 1 };
-        while current_height + self.compensated < available_area.height {
-            if let Some(added_height) = cell_height::<State>(ctx, index, id, available_area, cell) {
-                current_height += added_height;
+        while current_size + self.compensated < axis.extent(available_area) {
+            if let Some(added_size) = cell_size::<State>(ctx, index, id, available_area, axis, cell)
+            {
+                current_size += added_size;
                 self.visible_window.push(Element {
-                    height: added_height,
+                    size: added_size,
                     index,
                 });
                 index += 1;
@@ -58,16 +119,37 @@ impl ScrollerState {
         id: u64,
         cell: &dyn Fn(usize, u64, &mut AppCtx) -> Option<Layout<'a, View<State>, AppCtx>>,
     ) {
+        let axis = self.axis;
         if self.area != available_area && self.visible_window.len() > 1 {
             self.visible_window.drain(1..);
             let index = self.visible_window[0].index;
-            self.visible_window[0].height =
-                cell_height::<State>(ctx, index, id, available_area, cell).unwrap_or(0.);
+            self.visible_window[0].size =
+                cell_size::<State>(ctx, index, id, available_area, axis, cell).unwrap_or(0.);
             self.fill_forwards::<State>(ctx, available_area, id, cell);
         }
         if self.visible_window.is_empty() {
             self.fill_forwards::<State>(ctx, available_area, id, cell);
         }
+        let now = ctx.frame_time;
+        let elapsed = self.last_frame.map_or(0., |last| {
+            now.saturating_duration_since(last)
+                .as_secs_f32()
+                .min(MAX_FRAME_STEP)
+        });
+        self.last_frame = Some(now);
+        self.step_momentum(now, elapsed);
+        // Scrolling back from past an end takes up the overscroll first
+        if self.dt != 0. && self.overscroll != 0. && self.dt.signum() != self.overscroll.signum() {
+            let remaining = self.overscroll + self.dt;
+            if remaining.signum() == self.overscroll.signum() {
+                self.overscroll = remaining;
+                self.dt = 0.;
+            } else {
+                self.overscroll = 0.;
+                self.dt = remaining;
+            }
+        }
+        let mut excess = 0.;
         if self.dt != 0. {
             if self.dt.is_sign_negative() {
                 self.compensated += self.dt;
@@ -75,21 +157,23 @@ impl ScrollerState {
                 if self
                     .visible_window
                     .last()
-                    .and_then(|l| cell_height::<State>(ctx, l.index + 1, id, available_area, cell))
+                    .and_then(|l| {
+                        cell_size::<State>(ctx, l.index + 1, id, available_area, axis, cell)
+                    })
                     .is_none()
                 {
-                    self.compensated = self.compensated.max(
-                        available_area.height
-                            - self.visible_window.iter().fold(0., |acc, e| acc + e.height),
-                    );
+                    let end = axis.extent(available_area)
+                        - self.visible_window.iter().fold(0., |acc, e| acc + e.size);
+                    excess = (self.compensated - end).min(0.);
+                    self.compensated = self.compensated.max(end);
                 } else {
                     while let Some(true) = self
                         .visible_window
                         .first()
-                        .map(|first| first.height < -self.compensated && -self.compensated > 0.)
+                        .map(|first| first.size < -self.compensated && -self.compensated > 0.)
                     {
                         let removed = self.visible_window.remove(0);
-                        self.compensated += removed.height;
+                        self.compensated += removed.size;
                     }
                     self.fill_forwards::<State>(ctx, available_area, id, cell);
                 }
@@ -98,47 +182,132 @@ impl ScrollerState {
                 self.dt = 0.;
                 while let Some((ch, true, idx)) = self.visible_window.first().and_then(|f| {
                     if f.index > 0 {
-                        cell_height::<State>(ctx, f.index - 1, id, available_area, cell)
+                        cell_size::<State>(ctx, f.index - 1, id, available_area, axis, cell)
                             .map(|ch| (ch, self.compensated >= 0., f.index - 1))
                     } else {
-                        self.compensated = self.compensated.min(0.);
                         None
                     }
                 }) {
                     self.visible_window.insert(
                         0,
                         Element {
-                            height: ch,
+                            size: ch,
                             index: idx,
                         },
                     );
                     self.compensated -= ch;
                 }
+                if self.visible_window.first().is_some_and(|f| f.index == 0) {
+                    excess = self.compensated.max(0.);
+                    self.compensated = self.compensated.min(0.);
+                }
                 while self.visible_window.len() > 1
-                    && self.visible_window.iter().fold(0., |acc, e| acc + e.height)
-                        - self.visible_window.last().map(|l| l.height).unwrap_or(0.)
+                    && self.visible_window.iter().fold(0., |acc, e| acc + e.size)
+                        - self.visible_window.last().map(|l| l.size).unwrap_or(0.)
                         + self.compensated
-                        > available_area.height
+                        > axis.extent(available_area)
                 {
                     self.visible_window.pop();
                 }
             }
         }
-        self.offset = -(available_area.height
-            - self.visible_window.iter().fold(0., |acc, e| acc + e.height))
+        self.overscroll_by(excess, available_area);
+        if !self.tracking && !self.flinging && self.overscroll != 0. {
+            self.overscroll *= SPRING_BACK.powf(elapsed * 1000.);
+            if self.overscroll.abs() < 0.5 {
+                self.overscroll = 0.;
+            }
+        }
+        ctx.frame_requested |= self.flinging || (!self.tracking && self.overscroll != 0.);
+        self.offset = -(axis.extent(available_area)
+            - self.visible_window.iter().fold(0., |acc, e| acc + e.size))
             * 0.5;
         self.area = available_area;
     }
+
+    fn scrolled(&mut self, delta: ScrollDelta) {
+        match delta.phase {
+            TouchPhase::Started => {
+                self.tracking = true;
+                self.velocity = 0.;
+                self.last_sample = None;
+            }
+            TouchPhase::Ended => {
+                self.released = self.tracking;
+                self.tracking = false;
+            }
+            TouchPhase::Cancelled => self.tracking = false,
+            TouchPhase::Moved => (),
+        }
+        // Any new scrolling, including momentum the OS generates itself, stops a fling
+        self.flinging = false;
+        self.dt += match self.axis {
+            ScrollAxis::Vertical => delta.y,
+            // Most mouse wheels only scroll vertically
+            ScrollAxis::Horizontal if delta.x == 0. => delta.y,
+            ScrollAxis::Horizontal => delta.x,
+        };
+    }
+
+    /// Samples the velocity while tracking, starts a fling when the fingers lift and moves
+    /// the content along while flinging.
+    fn step_momentum(&mut self, now: Instant, elapsed: f32) {
+        if self.tracking && self.dt != 0. && elapsed > 0. {
+            let sample = self.dt / elapsed;
+            self.velocity = if self
+                .last_sample
+                .is_some_and(|last| now.saturating_duration_since(last) < VELOCITY_WINDOW)
+            {
+                sample * 0.6 + self.velocity * 0.4
+            } else {
+                sample
+            };
+            self.last_sample = Some(now);
+        }
+        if std::mem::take(&mut self.released) {
+            self.flinging = self
+                .last_sample
+                .is_some_and(|last| now.saturating_duration_since(last) < VELOCITY_WINDOW)
+                && self.velocity.abs() > MIN_FLING_VELOCITY;
+        }
+        if self.flinging {
+            self.dt += self.velocity * elapsed;
+            let deceleration = if self.overscroll == 0. {
+                FLING_DECELERATION
+            } else {
+                OVERSCROLL_DECELERATION
+            };
+            self.velocity *= deceleration.powf(elapsed * 1000.);
+            if self.velocity.abs() < MIN_FLING_VELOCITY {
+                self.flinging = false;
+                self.velocity = 0.;
+            }
+        }
+    }
+
+    /// Scrolling past an end stretches the content with increasing resistance during a
+    /// gesture or fling. Mouse wheels stop hard at the ends.
+    fn overscroll_by(&mut self, excess: f32, available_area: Area) {
+        if excess == 0. || !(self.tracking || self.flinging) {
+            return;
+        }
+        let range = (self.axis.extent(available_area) * 0.5).max(1.);
+        self.overscroll += excess * 0.5 / (1. + self.overscroll.abs() / range);
+    }
 }
 
-fn cell_height<'a, State>(
+fn cell_size<'a, State>(
     ctx: &mut AppCtx,
     index: usize,
     id: u64,
     available_area: Area,
+    axis: ScrollAxis,
     cell: &dyn Fn(usize, u64, &mut AppCtx) -> Option<Layout<'a, View<State>, AppCtx>>,
 ) -> Option<f32> {
-    cell(index, id, ctx).and_then(|mut layout| layout.min_height(available_area, ctx))
+    cell(index, id, ctx).and_then(|mut layout| match axis {
+        ScrollAxis::Vertical => layout.min_height(available_area, ctx),
+        ScrollAxis::Horizontal => layout.min_width(available_area, ctx),
+    })
 }
 
 pub fn scroller<'a, State: 'static>(
@@ -172,12 +341,17 @@ pub fn scroller<'a, State: 'static>(
                     let mut cells = Vec::new();
                     for element in &s.visible_window {
                         if let Some(c) = cell(element.index, id, ctx) {
-                            cells.push(c.height(element.height));
+                            cells.push(match s.axis {
+                                ScrollAxis::Vertical => c.height(element.size),
+                                ScrollAxis::Horizontal => c.width(element.size),
+                            });
                         }
                     }
-                    let offset = s.offset;
-                    let comp = s.compensated;
-                    column(cells).offset_y(offset + comp).draw(area, ctx)
+                    let offset = s.offset + s.compensated + s.overscroll;
+                    match s.axis {
+                        ScrollAxis::Vertical => column(cells).offset_y(offset).draw(area, ctx),
+                        ScrollAxis::Horizontal => row(cells).offset_x(offset).draw(area, ctx),
+                    }
                 }
             })
             .expand(),
@@ -187,11 +361,155 @@ pub fn scroller<'a, State: 'static>(
             .fill(TRANSPARENT)
             .view()
             .on_scroll({
-                move |_s: &mut State, _app: &mut AppState, dt| {
-                    let mut state = scroll_state.borrow_mut();
-                    state.dt += dt.y;
+                move |_s: &mut State, _app: &mut AppState, delta| {
+                    scroll_state.borrow_mut().scrolled(delta);
                 }
             })
             .finish(ctx),
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{MIN_FLING_VELOCITY, ScrollerState};
+    use crate::*;
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    #[derive(Clone, Default)]
+    struct State {
+        button: ButtonState,
+        scroller: Rc<RefCell<ScrollerState>>,
+    }
+
+    fn view<'a>(state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        scroller(
+            crate::id!(),
+            None,
+            state.scroller.clone(),
+            move |index, _id, ctx| {
+                (index == 0).then(|| {
+                    button(crate::id!(), crate::binding!(state, State, button))
+                        .text_label("Cell")
+                        .build(ctx)
+                        .height(40.)
+                })
+            },
+            app.ctx(),
+        )
+    }
+
+    #[test]
+    fn cells_are_hovered_under_the_scroll_overlay() {
+        let mut app = TestApp::new(State::default(), view).size(200., 200.);
+        app.move_pointer(100., 20.);
+        assert!(app.state.button.hovered);
+        assert_eq!(app.cursor(), CursorIcon::Pointer);
+        app.move_pointer(100., 150.);
+        assert!(!app.state.button.hovered);
+    }
+
+    #[test]
+    fn horizontal_scroller_takes_vertical_wheel() {
+        let mut scroller = ScrollerState::default().axis(ScrollAxis::Horizontal);
+        let wheel = |x, y| ScrollDelta {
+            x,
+            y,
+            phase: TouchPhase::Moved,
+            unit: ScrollUnit::Line,
+        };
+        scroller.scrolled(wheel(0., 10.));
+        assert_eq!(scroller.dt, 10.);
+        scroller.scrolled(wheel(5., 10.));
+        assert_eq!(scroller.dt, 15.);
+    }
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    /// A hundred cells 40 high, in a 200 high window.
+    fn list_view<'a>(state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        scroller(
+            crate::id!(),
+            None,
+            state.scroller.clone(),
+            |index, _id, ctx| {
+                (index < 100).then(|| {
+                    rect(crate::id!(index as u64))
+                        .fill(TRANSPARENT)
+                        .view()
+                        .finish(ctx)
+                        .height(40.)
+                })
+            },
+            app.ctx(),
+        )
+    }
+
+    fn list() -> TestApp<State> {
+        let mut app = TestApp::new(State::default(), list_view).size(100., 200.);
+        app.move_pointer(50., 100.);
+        app
+    }
+
+    /// How far the list has scrolled from the top, ignoring overscroll.
+    fn position(app: &TestApp<State>) -> f32 {
+        let scroller = app.state.scroller.borrow();
+        let first = scroller.visible_window.first().map_or(0, |e| e.index);
+        first as f32 * 40. - scroller.compensated
+    }
+
+    #[test]
+    fn fling_keeps_scrolling_then_stops() {
+        let mut app = list();
+        app.trackpad_scroll(0., 0., TouchPhase::Started);
+        for _ in 0..5 {
+            app.advance(FRAME);
+            app.trackpad_scroll(0., -20., TouchPhase::Moved);
+        }
+        app.trackpad_scroll(0., 0., TouchPhase::Ended);
+        let lifted = position(&app);
+        assert_eq!(lifted, 100.);
+        assert!(app.state.scroller.borrow().velocity.abs() > MIN_FLING_VELOCITY);
+        app.advance(FRAME);
+        assert!(position(&app) > lifted);
+        for _ in 0..300 {
+            app.advance(FRAME);
+        }
+        let stopped = position(&app);
+        assert!(!app.state.scroller.borrow().flinging);
+        assert_eq!(app.state.scroller.borrow().velocity, 0.);
+        app.advance(FRAME);
+        assert_eq!(position(&app), stopped);
+    }
+
+    #[test]
+    fn overscroll_springs_back_to_the_end() {
+        let mut app = list();
+        app.trackpad_scroll(0., 0., TouchPhase::Started);
+        app.advance(FRAME);
+        app.trackpad_scroll(0., 100., TouchPhase::Moved);
+        assert!(app.state.scroller.borrow().overscroll > 0.);
+        // Resting before the lift means there's no fling
+        app.advance(Duration::from_millis(200));
+        app.trackpad_scroll(0., 0., TouchPhase::Ended);
+        for _ in 0..200 {
+            app.advance(FRAME);
+        }
+        assert_eq!(app.state.scroller.borrow().overscroll, 0.);
+        assert_eq!(position(&app), 0.);
+    }
+
+    #[test]
+    fn wheel_stops_hard_at_the_ends() {
+        let mut app = list();
+        app.scroll(0., 500.);
+        assert_eq!(app.state.scroller.borrow().overscroll, 0.);
+        assert_eq!(position(&app), 0.);
+        for _ in 0..150 {
+            app.scroll(0., -40.);
+        }
+        assert_eq!(app.state.scroller.borrow().overscroll, 0.);
+        assert_eq!(position(&app), 100. * 40. - 200.);
+        app.scroll(0., -40.);
+        assert_eq!(position(&app), 100. * 40. - 200.);
+    }
+}
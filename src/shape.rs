@@ -3,25 +3,81 @@ use std::rc::Rc;
 use crate::background_style::BrushSource;
 use backer::Area;
 use vello_svg::vello::Scene;
-use vello_svg::vello::kurbo::{Affine, BezPath, Point, RoundedRect, Shape as _, Stroke};
-use vello_svg::vello::peniko::{Color, Fill};
+use vello_svg::vello::kurbo::{
+    Affine, BezPath, Point, Rect, RoundedRect, Shape as _, Stroke, Vec2,
+};
+use vello_svg::vello::peniko::{Color, Fill, Mix};
 
 pub(crate) type PathBuilder = Rc<dyn Fn(Area) -> BezPath>;
 
+/// Strokes used to approximate the blur of shadows that aren't rounded rects.
+const SHADOW_STEPS: usize = 8;
+/// Copies of the backdrop per ring when blurring it. Two rings and the center are drawn.
+const BACKDROP_RING_TAPS: usize = 6;
+
 #[derive(Clone)]
 pub struct PathData {
     pub(crate) id: u64,
     pub(crate) builder: PathBuilder,
     pub(crate) fill: Option<BrushSource<()>>,
     pub(crate) stroke: Option<(BrushSource<()>, Stroke)>,
+    pub(crate) shadows: Vec<Shadow>,
+    pub(crate) shadow_shape: ShadowShape,
+    /// Blurs what was drawn before the shape, inside it, by this radius.
+    pub(crate) backdrop_blur: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Shadow {
+    pub(crate) color: Color,
+    pub(crate) offset: (f32, f32),
+    pub(crate) blur_radius: f32,
+    pub(crate) spread: f32,
+    /// Drawn inside the shape, over its fill.
+    pub(crate) inset: bool,
+}
+
+/// Shapes whose drop shadows can be drawn exactly as a blurred rounded rect. Other paths
+/// get a layered approximation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ShadowShape {
+    RoundedRect(f32),
+    Circle,
+    Path,
+}
+
+/// Renderer independent drawing of shadows, in logical coordinates.
+pub(crate) enum ShadowOp {
+    BlurredRect {
+        rect: Rect,
+        radius: f64,
+        std_dev: f64,
+        color: Color,
+    },
+    Fill(BezPath, Fill, Color),
+    Stroke(BezPath, f64, Color),
+    PushClip(BezPath, Fill),
+    PopClip,
 }
 
 impl PathData {
-    pub(crate) fn draw(&self, scene: &mut Scene, area: Area, scale_factor: f64) {
+    /// `open_layers` is the number of clip and blend layers pushed onto `scene` and not yet
+    /// popped.
+    pub(crate) fn draw(
+        &self,
+        scene: &mut Scene,
+        area: Area,
+        scale_factor: f64,
+        open_layers: usize,
+    ) {
         let user_path = (self.builder)(area);
         let scale = Affine::scale(scale_factor);
         let path = scale * &user_path;
 
+        draw_shadows(scene, self.shadow_ops(area, false), scale);
+        if let Some(radius) = self.backdrop_blur {
+            blur_backdrop(scene, &user_path, radius as f64, scale_factor, open_layers);
+        }
         if self.fill.is_none() && self.stroke.is_none() {
             scene.fill(Fill::EvenOdd, Affine::IDENTITY, Color::BLACK, None, &path)
         } else {
@@ -29,12 +85,152 @@ impl PathData {
                 let brush = brush_source.resolve(area, &());
                 scene.fill(Fill::EvenOdd, scale, &brush, None, &user_path)
             }
+            draw_shadows(scene, self.shadow_ops(area, true), scale);
             if let Some((ref brush_source, ref stroke_style)) = self.stroke {
                 let brush = brush_source.resolve(area, &());
                 scene.stroke(stroke_style, scale, &brush, None, &user_path);
             }
         }
     }
+
+    /// The drop shadows, or with `inset` the inner shadows, of the shape in `area`.
+    pub(crate) fn shadow_ops(&self, area: Area, inset: bool) -> Vec<ShadowOp> {
+        let mut ops = Vec::new();
+        for shadow in self.shadows.iter().filter(|shadow| shadow.inset == inset) {
+            let offset = Vec2::new(shadow.offset.0 as f64, shadow.offset.1 as f64);
+            let blur = shadow.blur_radius.max(0.) as f64;
+            let spread = shadow.spread as f64;
+            let path = Affine::translate(offset) * (self.builder)(area);
+            let margin = 2. * blur + spread.abs() + offset.x.abs() + offset.y.abs() + 1.;
+            let mut outside = area_rect(area).inflate(margin, margin).to_path(0.1);
+            outside.extend(path.elements().iter().copied());
+            if inset {
+                ops.push(ShadowOp::PushClip((self.builder)(area), Fill::EvenOdd));
+                ops.push(ShadowOp::Fill(outside, Fill::EvenOdd, shadow.color));
+                ops.push(ShadowOp::PushClip(path.clone(), Fill::EvenOdd));
+                edge_strokes(&mut ops, &path, shadow, blur, spread);
+                ops.push(ShadowOp::PopClip);
+                ops.push(ShadowOp::PopClip);
+            } else if let Some((rect, radius)) = self.blur_rect(area)
+                && blur > 0.
+            {
+                ops.push(ShadowOp::BlurredRect {
+                    rect: rect.inflate(spread, spread) + offset,
+                    radius: (radius + spread).max(0.),
+                    std_dev: blur * 0.5,
+                    color: shadow.color,
+                });
+            } else {
+                ops.push(ShadowOp::Fill(path.clone(), Fill::EvenOdd, shadow.color));
+                ops.push(ShadowOp::PushClip(outside, Fill::EvenOdd));
+                edge_strokes(&mut ops, &path, shadow, blur, spread);
+                ops.push(ShadowOp::PopClip);
+            }
+        }
+        ops
+    }
+
+    fn blur_rect(&self, area: Area) -> Option<(Rect, f64)> {
+        let rect = area_rect(area);
+        match self.shadow_shape {
+            ShadowShape::RoundedRect(radius) => Some((
+                rect,
+                (radius as f64).min(rect.width().min(rect.height()) * 0.5),
+            )),
+            ShadowShape::Circle => {
+                let side = rect.width().min(rect.height());
+                Some((
+                    Rect::from_center_size(rect.center(), (side, side)),
+                    side * 0.5,
+                ))
+            }
+            ShadowShape::Path => None,
+        }
+    }
+}
+
+fn area_rect(area: Area) -> Rect {
+    Rect::from_origin_size(
+        Point::new(area.x as f64, area.y as f64),
+        (area.width as f64, area.height as f64),
+    )
+}
+
+/// Strokes of growing width along the edge of `path`. Where they all overlap the shadow
+/// reaches its full color, fading out over the blur radius.
+fn edge_strokes(ops: &mut Vec<ShadowOp>, path: &BezPath, shadow: &Shadow, blur: f64, spread: f64) {
+    let steps = if blur > 0. { SHADOW_STEPS } else { 1 };
+    // Each stroke's alpha is chosen so that all of them together composite to the shadow's
+    let alpha = 1. - (1. - shadow.color.components[3]).powf(1. / steps as f32);
+    let color = shadow.color.with_alpha(alpha);
+    for step in 1..=steps {
+        let width = 2. * spread + 2. * blur * step as f64 / steps as f64;
+        if width > 0. {
+            ops.push(ShadowOp::Stroke(path.clone(), width, color));
+        }
+    }
+}
+
+/// Approximates a blur of everything drawn so far inside `clip` by averaging copies of the
+/// scene shifted around a ring, as vello has no blur filter.
+fn blur_backdrop(
+    scene: &mut Scene,
+    clip: &BezPath,
+    radius: f64,
+    scale_factor: f64,
+    open_layers: usize,
+) {
+    if radius <= 0. {
+        return;
+    }
+    let scale = Affine::scale(scale_factor);
+    let backdrop = scene.clone();
+    scene.push_layer(Fill::EvenOdd, Mix::Normal, 1., scale, clip);
+    for (i, offset) in backdrop_offsets(radius, scale_factor)
+        .into_iter()
+        .enumerate()
+    {
+        // Each copy covers the average of those before it at 1/n, so all count equally
+        scene.push_layer(Fill::EvenOdd, Mix::Normal, 1. / (i + 1) as f32, scale, clip);
+        scene.append(&backdrop, Some(Affine::translate(offset)));
+        // The copy still has the layers enclosing this shape open
+        for _ in 0..open_layers {
+            scene.pop_layer();
+        }
+        scene.pop_layer();
+    }
+    scene.pop_layer();
+}
+
+/// Where the copies of the backdrop are drawn when blurring it, in physical pixels.
+pub(crate) fn backdrop_offsets(radius: f64, scale_factor: f64) -> Vec<Vec2> {
+    let mut offsets = vec![Vec2::ZERO];
+    for ring in [1., 0.5] {
+        for tap in 0..BACKDROP_RING_TAPS {
+            let angle = std::f64::consts::TAU * (tap as f64 + ring) / BACKDROP_RING_TAPS as f64;
+            offsets.push(Vec2::from_angle(angle) * radius * ring * scale_factor);
+        }
+    }
+    offsets
+}
+
+fn draw_shadows(scene: &mut Scene, ops: Vec<ShadowOp>, scale: Affine) {
+    for op in ops {
+        match op {
+            ShadowOp::BlurredRect {
+                rect,
+                radius,
+                std_dev,
+                color,
+            } => scene.draw_blurred_rounded_rect(scale, rect, color, radius, std_dev),
+            ShadowOp::Fill(path, fill, color) => scene.fill(fill, scale, color, None, &path),
+            ShadowOp::Stroke(path, width, color) => {
+                scene.stroke(&Stroke::new(width), scale, color, None, &path)
+            }
+            ShadowOp::PushClip(path, fill) => scene.push_layer(fill, Mix::Normal, 1., scale, &path),
+            ShadowOp::PopClip => scene.pop_layer(),
+        }
+    }
 }
 
 pub(crate) fn rect_path(corner_rounding: (f32, f32, f32, f32)) -> PathBuilder {
@@ -0,0 +1,328 @@
+use crate::app::AppState;
+use crate::{Key, KeyEvent};
+use std::any::Any;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::str::FromStr;
+use winit::keyboard::{KeyCode, ModifiersState, NamedKey, PhysicalKey};
+
+/// The modifier used for application shortcuts: Super (Cmd) on macOS and Control elsewhere.
+pub fn primary_modifier() -> ModifiersState {
+    if cfg!(target_os = "macos") {
+        ModifiersState::SUPER
+    } else {
+        ModifiersState::CONTROL
+    }
+}
+
+/// A key combination such as `"CmdOrCtrl+S"` or `"Ctrl+Shift+P"`.
+///
+/// `CmdOrCtrl`, `Primary` and `Mod` name the platform's primary modifier, so the
+/// same accelerator means Cmd+S on macOS and Ctrl+S elsewhere. `Ctrl`/`Control`
+/// always name the Control key, `Cmd`/`Super`/`Meta` the Super key, `Alt`/`Option`
+/// the Alt key and `Shift` the Shift key. Matching is exact: `"Ctrl+S"` does not
+/// fire for Ctrl+Shift+S.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accelerator {
+    modifiers: ModifiersState,
+    key: Key,
+}
+
+impl Accelerator {
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let modifiers = event.modifiers
+            & (ModifiersState::SHIFT
+                | ModifiersState::CONTROL
+                | ModifiersState::ALT
+                | ModifiersState::SUPER);
+        if modifiers != self.modifiers {
+            return false;
+        }
+        match (&self.key, &event.key) {
+            (Key::Named(a), Key::Named(b)) => a == b,
+            (Key::Character(a), Key::Character(b)) if a.eq_ignore_ascii_case(b) => true,
+            // Alt and Shift can change the character a key produces, so fall back to the
+            // key's position for letters and digits. Otherwise the position only counts when
+            // the key produced no character, so layouts that move letters still match by name.
+            (Key::Character(a), key) => {
+                let shifted = self
+                    .modifiers
+                    .intersects(ModifiersState::ALT | ModifiersState::SHIFT);
+                (shifted || !matches!(key, Key::Character(_)))
+                    && character_code(a)
+                        .is_some_and(|code| event.physical_key == PhysicalKey::Code(code))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Accelerator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = ModifiersState::empty();
+        let mut key = None;
+        for part in s.split('+').map(str::trim) {
+            if key.is_some() {
+                return Err(format!("`{s}`: the key must come after all modifiers"));
+            }
+            match part.to_ascii_lowercase().as_str() {
+                "cmdorctrl" | "primary" | "mod" => modifiers |= primary_modifier(),
+                "ctrl" | "control" => modifiers |= ModifiersState::CONTROL,
+                "cmd" | "command" | "super" | "meta" | "win" => modifiers |= ModifiersState::SUPER,
+                "alt" | "option" | "opt" => modifiers |= ModifiersState::ALT,
+                "shift" => modifiers |= ModifiersState::SHIFT,
+                "" => return Err(format!("`{s}`: empty key")),
+                _ => {
+                    key = Some(
+                        parse_key(part).ok_or_else(|| format!("`{s}`: unknown key `{part}`"))?,
+                    )
+                }
+            }
+        }
+        let key = key.ok_or_else(|| format!("`{s}`: missing key"))?;
+        Ok(Accelerator { modifiers, key })
+    }
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    let named = match name.to_ascii_lowercase().as_str() {
+        "enter" | "return" => NamedKey::Enter,
+        "esc" | "escape" => NamedKey::Escape,
+        "tab" => NamedKey::Tab,
+        "space" => NamedKey::Space,
+        "backspace" => NamedKey::Backspace,
+        "delete" | "del" => NamedKey::Delete,
+        "insert" => NamedKey::Insert,
+        "up" | "arrowup" => NamedKey::ArrowUp,
+        "down" | "arrowdown" => NamedKey::ArrowDown,
+        "left" | "arrowleft" => NamedKey::ArrowLeft,
+        "right" | "arrowright" => NamedKey::ArrowRight,
+        "home" => NamedKey::Home,
+        "end" => NamedKey::End,
+        "pageup" => NamedKey::PageUp,
+        "pagedown" => NamedKey::PageDown,
+        "f1" => NamedKey::F1,
+        "f2" => NamedKey::F2,
+        "f3" => NamedKey::F3,
+        "f4" => NamedKey::F4,
+        "f5" => NamedKey::F5,
+        "f6" => NamedKey::F6,
+        "f7" => NamedKey::F7,
+        "f8" => NamedKey::F8,
+        "f9" => NamedKey::F9,
+        "f10" => NamedKey::F10,
+        "f11" => NamedKey::F11,
+        "f12" => NamedKey::F12,
+        _ => {
+            let mut chars = name.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(Key::Character(c.to_lowercase().to_string().into())),
+                _ => None,
+            };
+        }
+    };
+    Some(Key::Named(named))
+}
+
+fn character_code(c: &str) -> Option<KeyCode> {
+    Some(match c {
+        "a" => KeyCode::KeyA,
+        "b" => KeyCode::KeyB,
+        "c" => KeyCode::KeyC,
+        "d" => KeyCode::KeyD,
+        "e" => KeyCode::KeyE,
+        "f" => KeyCode::KeyF,
+        "g" => KeyCode::KeyG,
+        "h" => KeyCode::KeyH,
+        "i" => KeyCode::KeyI,
+        "j" => KeyCode::KeyJ,
+        "k" => KeyCode::KeyK,
+        "l" => KeyCode::KeyL,
+        "m" => KeyCode::KeyM,
+        "n" => KeyCode::KeyN,
+        "o" => KeyCode::KeyO,
+        "p" => KeyCode::KeyP,
+        "q" => KeyCode::KeyQ,
+        "r" => KeyCode::KeyR,
+        "s" => KeyCode::KeyS,
+        "t" => KeyCode::KeyT,
+        "u" => KeyCode::KeyU,
+        "v" => KeyCode::KeyV,
+        "w" => KeyCode::KeyW,
+        "x" => KeyCode::KeyX,
+        "y" => KeyCode::KeyY,
+        "z" => KeyCode::KeyZ,
+        "0" => KeyCode::Digit0,
+        "1" => KeyCode::Digit1,
+        "2" => KeyCode::Digit2,
+        "3" => KeyCode::Digit3,
+        "4" => KeyCode::Digit4,
+        "5" => KeyCode::Digit5,
+        "6" => KeyCode::Digit6,
+        "7" => KeyCode::Digit7,
+        "8" => KeyCode::Digit8,
+        "9" => KeyCode::Digit9,
+        _ => return None,
+    })
+}
+
+#[derive(Clone)]
+pub(crate) struct Shortcut {
+    pub(crate) accelerator: Accelerator,
+    pub(crate) handler: Rc<dyn Fn(&mut dyn Any, &mut AppState)>,
+}
+
+impl Debug for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shortcut")
+            .field("accelerator", &self.accelerator)
+            .finish()
+    }
+}
+
+impl Shortcut {
+    pub(crate) fn new<State: 'static>(
+        accelerator: Accelerator,
+        f: impl Fn(&mut State, &mut AppState) + 'static,
+    ) -> Self {
+        Shortcut {
+            accelerator,
+            handler: Rc::new(move |state, app| {
+                let state = state.downcast_mut::<State>();
+                debug_assert!(
+                    state.is_some(),
+                    "shortcut callback takes `{}`, which isn't the app's state type",
+                    std::any::type_name::<State>()
+                );
+                if let Some(state) = state {
+                    f(state, app);
+                }
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::ElementState;
+
+    fn press(key: Key, code: KeyCode, modifiers: ModifiersState) -> KeyEvent {
+        KeyEvent {
+            key,
+            physical_key: PhysicalKey::Code(code),
+            state: ElementState::Pressed,
+            is_repeat: false,
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn physical_fallback_needs_alt_shift_or_no_character() {
+        let primary_z: Accelerator = "Primary+Z".parse().unwrap();
+        // Z on a QWERTZ layout sits where QWERTY has Y
+        assert!(!primary_z.matches(&press(
+            Key::Character("y".into()),
+            KeyCode::KeyZ,
+            primary_modifier()
+        )));
+        assert!(primary_z.matches(&press(
+            Key::Character("z".into()),
+            KeyCode::KeyY,
+            primary_modifier()
+        )));
+        assert!(primary_z.matches(&press(
+            Key::Named(NamedKey::Escape),
+            KeyCode::KeyZ,
+            primary_modifier()
+        )));
+        let alt_p: Accelerator = "Alt+P".parse().unwrap();
+        assert!(alt_p.matches(&press(
+            Key::Character("π".into()),
+            KeyCode::KeyP,
+            ModifiersState::ALT
+        )));
+    }
+
+    #[test]
+    fn parses_modifiers_and_key() {
+        let accelerator: Accelerator = "CmdOrCtrl+Shift+P".parse().unwrap();
+        assert_eq!(
+            accelerator,
+            Accelerator {
+                modifiers: primary_modifier() | ModifiersState::SHIFT,
+                key: Key::Character("p".into()),
+            }
+        );
+        let accelerator: Accelerator = "alt + esc".parse().unwrap();
+        assert_eq!(accelerator.modifiers, ModifiersState::ALT);
+        assert_eq!(accelerator.key, Key::Named(NamedKey::Escape));
+        assert_eq!(
+            "Super+F5".parse::<Accelerator>().unwrap().key,
+            Key::Named(NamedKey::F5)
+        );
+    }
+
+    #[test]
+    fn ctrl_is_control_and_cmd_is_super_on_every_platform() {
+        let modifiers = |s: &str| s.parse::<Accelerator>().unwrap().modifiers;
+        assert_eq!(modifiers("Ctrl+S"), ModifiersState::CONTROL);
+        assert_eq!(modifiers("control+s"), ModifiersState::CONTROL);
+        assert_eq!(modifiers("Cmd+S"), ModifiersState::SUPER);
+        assert_eq!(modifiers("Command+S"), ModifiersState::SUPER);
+        assert_eq!(modifiers("Mod+S"), primary_modifier());
+        assert_eq!(modifiers("Primary+S"), primary_modifier());
+    }
+
+    #[test]
+    fn rejects_malformed_accelerators() {
+        assert!("Ctrl+".parse::<Accelerator>().is_err());
+        assert!("Ctrl+Shift".parse::<Accelerator>().is_err());
+        assert!("S+Ctrl".parse::<Accelerator>().is_err());
+        assert!("Ctrl+Hyper".parse::<Accelerator>().is_err());
+    }
+
+    #[test]
+    fn modifiers_match_exactly() {
+        let save: Accelerator = "Ctrl+S".parse().unwrap();
+        let s = Key::Character("s".into());
+        assert!(save.matches(&press(s.clone(), KeyCode::KeyS, ModifiersState::CONTROL)));
+        assert!(save.matches(&press(
+            Key::Character("S".into()),
+            KeyCode::KeyS,
+            ModifiersState::CONTROL
+        )));
+        assert!(!save.matches(&press(s.clone(), KeyCode::KeyS, ModifiersState::empty())));
+        assert!(!save.matches(&press(s.clone(), KeyCode::KeyS, ModifiersState::SUPER)));
+        assert!(!save.matches(&press(
+            s,
+            KeyCode::KeyS,
+            ModifiersState::CONTROL | ModifiersState::SHIFT
+        )));
+        let escape: Accelerator = "Escape".parse().unwrap();
+        assert!(escape.matches(&press(
+            Key::Named(NamedKey::Escape),
+            KeyCode::Escape,
+            ModifiersState::empty()
+        )));
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't the app's state type")]
+    #[cfg(debug_assertions)]
+    fn shortcut_for_another_state_type_panics() {
+        fn view<'a>(
+            _state: &'a (),
+            _app: &mut AppState,
+        ) -> crate::Layout<'a, crate::View<()>, crate::AppCtx> {
+            crate::space()
+        }
+        let mut app = crate::TestApp::new((), view);
+        app.app_state()
+            .register_shortcut("Escape", |_: &mut u32, _app| ())
+            .unwrap();
+        app.key(Key::Named(NamedKey::Escape));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     Binding, DEFAULT_DARK_GRAY, DEFAULT_FG, DEFAULT_GRAY, DEFAULT_PURP, DragState, TRANSPARENT,
-    adjust_brush,
+    Transition, adjust_brush,
     app::{AppCtx, AppState, View},
     circle, id, rect,
 };
@@ -114,6 +114,11 @@ impl<'a, State> Slider<'a, State> {
             let height = area.height;
             let normalized_value = (state.value - self.min) / (self.max - self.min);
             let slider_width = (width - height) * normalized_value + height;
+            let knob_scale = ctx.animate(
+                id!(id),
+                if state.dragging { 1.1 } else { 1. },
+                Transition::default(),
+            );
 
             let bg = if let Some(ref f) = background_fn {
                 f(state, area, ctx)
@@ -161,7 +166,7 @@ impl<'a, State> Slider<'a, State> {
                     .width(slider_width)
                     .offset((-width * 0.5) + (slider_width * 0.5), 0.),
                 knob.pad(height * 0.1)
-                    .height(if state.dragging { height * 1.1 } else { height })
+                    .height(height * knob_scale)
                     .width(height)
                     .offset((-width * 0.5) + slider_width - (height * 0.5), 0.),
                 rect(id!(id))
@@ -220,3 +225,40 @@ impl<'a, State> Slider<'a, State> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone, Default)]
+    struct State {
+        slider: SliderState,
+        changes: u32,
+    }
+
+    fn view<'a>(state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        slider(crate::id!(), crate::binding!(state, State, slider))
+            .range(0., 10.)
+            .on_change(|state: &mut State, _app, _value| state.changes += 1)
+            .build(app.ctx())
+    }
+
+    #[test]
+    fn drag_sets_value() {
+        // The track leaves a knob's width of padding at each end, 20 to 180 here
+        let mut app = TestApp::new(State::default(), view).size(200., 20.);
+        app.drag((20., 10.), (100., 10.), 4);
+        assert!((app.state.slider.value - 5.).abs() < 0.01);
+        assert!(!app.state.slider.dragging);
+        assert!(app.state.changes > 0);
+    }
+
+    #[test]
+    fn drag_clamps_to_range() {
+        let mut app = TestApp::new(State::default(), view).size(200., 20.);
+        app.drag((100., 10.), (300., 10.), 4);
+        assert_eq!(app.state.slider.value, 10.);
+        app.drag((100., 10.), (-100., 10.), 4);
+        assert_eq!(app.state.slider.value, 0.);
+    }
+}
@@ -0,0 +1,465 @@
+use crate::app::{AppState, EditState, View};
+use crate::view::DrawableType;
+use backer::Area;
+use std::fmt::{self, Display, Formatter, Write as _};
+use vello_svg::vello::kurbo::{Affine, Rect, Shape as _};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewKind {
+    Text,
+    Path,
+    Svg,
+    Image,
+}
+
+impl ViewKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ViewKind::Text => "text",
+            ViewKind::Path => "path",
+            ViewKind::Svg => "svg",
+            ViewKind::Image => "image",
+        }
+    }
+}
+
+/// A single drawable of a laid-out frame.
+#[derive(Debug, Clone)]
+pub struct ViewNode {
+    pub id: u64,
+    pub kind: ViewKind,
+    /// In window coordinates. Views inside a `transform` get the bounds of their transformed
+    /// area.
+    pub area: Area,
+    /// The string of text views, or the contents of the editor for text being edited.
+    pub text: Option<String>,
+    /// Indices into `FrameSnapshot::clips` of the clips enclosing this view, outermost first.
+    pub clips: Vec<usize>,
+    /// Names of the interactions the view handles, e.g. `"click"` or `"hover"`.
+    pub interactions: Vec<&'static str>,
+}
+
+/// A structured copy of the draw list produced by `Layout::draw`, in paint order.
+///
+/// `Display` writes one line per view, indented by clip nesting, and `to_json`
+/// writes the same information as JSON. Both forms are stable across runs so
+/// they can be compared against checked in snapshots.
+#[derive(Debug, Clone, Default)]
+pub struct FrameSnapshot {
+    pub nodes: Vec<ViewNode>,
+    /// Bounding boxes of the clip paths pushed while drawing the frame.
+    pub clips: Vec<Area>,
+}
+
+impl FrameSnapshot {
+    pub fn capture<State>(views: &[View<State>], app: &AppState) -> Self {
+        let mut snapshot = FrameSnapshot::default();
+        let mut clip_stack = Vec::new();
+        let mut transform_stack = Vec::new();
+        let mut transform = Affine::IDENTITY;
+        for item in views {
+            match item {
+                View::PushClip { path } => {
+                    clip_stack.push(snapshot.clips.len());
+                    snapshot
+                        .clips
+                        .push(rect_area((transform * path.clone()).bounding_box()));
+                }
+                View::PopClip => {
+                    clip_stack.pop();
+                }
+                View::Draw {
+                    view,
+                    gesture_handlers,
+                    area,
+                } => {
+                    let (kind, text) = match view.as_ref() {
+                        DrawableType::Text(text) => (ViewKind::Text, Some(text.string.clone())),
+                        // Laid out text is only drawn directly by the active editor
+                        DrawableType::Layout(_) => (
+                            ViewKind::Text,
+                            app.app_context
+                                .editor
+                                .as_ref()
+                                .map(|EditState { editor, .. }| {
+                                    editor.text().into_iter().collect::<String>()
+                                }),
+                        ),
+                        DrawableType::Path(_) => (ViewKind::Path, None),
+                        DrawableType::Svg(_) => (ViewKind::Svg, None),
+                        DrawableType::Image(_) => (ViewKind::Image, None),
+                    };
+                    let mut interactions = Vec::new();
+                    for handler in gesture_handlers {
+                        for name in handler.interaction_type.names() {
+                            if !interactions.contains(&name) {
+                                interactions.push(name);
+                            }
+                        }
+                    }
+                    snapshot.nodes.push(ViewNode {
+                        id: view.id(),
+                        kind,
+                        area: if transform == Affine::IDENTITY {
+                            *area
+                        } else {
+                            let rect = Rect::new(
+                                area.x as f64,
+                                area.y as f64,
+                                (area.x + area.width) as f64,
+                                (area.y + area.height) as f64,
+                            );
+                            rect_area(transform.transform_rect_bbox(rect))
+                        },
+                        text,
+                        clips: clip_stack.clone(),
+                        interactions,
+                    });
+                }
+                View::PushTransform { transform: pushed } => {
+                    transform_stack.push(transform);
+                    transform *= *pushed;
+                }
+                View::PopTransform => {
+                    transform = transform_stack.pop().unwrap_or_default();
+                }
+                View::PushLayer { .. } | View::PopLayer => (),
+                View::EditorArea(..) | View::Empty => (),
+            }
+        }
+        snapshot
+    }
+
+    pub fn find(&self, id: u64) -> Option<&ViewNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn find_all(&self, id: u64) -> impl Iterator<Item = &ViewNode> {
+        self.nodes.iter().filter(move |node| node.id == id)
+    }
+
+    /// The first text view whose text is exactly `text`.
+    pub fn find_text(&self, text: &str) -> Option<&ViewNode> {
+        self.nodes
+            .iter()
+            .find(|node| node.kind == ViewKind::Text && node.text.as_deref() == Some(text))
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n  \"clips\": [");
+        for (i, clip) in self.clips.iter().enumerate() {
+            let _ = write!(
+                out,
+                "{}\n    {}",
+                if i == 0 { "" } else { "," },
+                json_area(clip)
+            );
+        }
+        out.push_str(if self.clips.is_empty() {
+            "],\n"
+        } else {
+            "\n  ],\n"
+        });
+        out.push_str("  \"nodes\": [");
+        for (i, node) in self.nodes.iter().enumerate() {
+            // Ids are written as strings since they don't fit in a JSON double
+            let _ = write!(
+                out,
+                "{}\n    {{\"id\": \"{}\", \"kind\": \"{}\", \"area\": {}, \"text\": {}, \"clips\": [{}], \"interactions\": [{}]}}",
+                if i == 0 { "" } else { "," },
+                node.id,
+                node.kind.name(),
+                json_area(&node.area),
+                node.text
+                    .as_deref()
+                    .map(json_string)
+                    .unwrap_or_else(|| "null".to_string()),
+                node.clips
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                node.interactions
+                    .iter()
+                    .map(|name| json_string(name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+        }
+        out.push_str(if self.nodes.is_empty() {
+            "]\n}\n"
+        } else {
+            "\n  ]\n}\n"
+        });
+        out
+    }
+}
+
+impl Display for FrameSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut open: Vec<usize> = Vec::new();
+        for node in &self.nodes {
+            let shared = open
+                .iter()
+                .zip(&node.clips)
+                .take_while(|(a, b)| a == b)
+                .count();
+            open.truncate(shared);
+            for clip in &node.clips[shared..] {
+                writeln!(
+                    f,
+                    "{:indent$}clip {}",
+                    "",
+                    fmt_area(&self.clips[*clip]),
+                    indent = open.len() * 2
+                )?;
+                open.push(*clip);
+            }
+            write!(
+                f,
+                "{:indent$}{} #{} {}",
+                "",
+                node.kind.name(),
+                node.id,
+                fmt_area(&node.area),
+                indent = open.len() * 2
+            )?;
+            if let Some(text) = &node.text {
+                write!(f, " {text:?}")?;
+            }
+            if !node.interactions.is_empty() {
+                write!(f, " [{}]", node.interactions.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn rect_area(rect: Rect) -> Area {
+    Area {
+        x: rect.x0 as f32,
+        y: rect.y0 as f32,
+        width: rect.width() as f32,
+        height: rect.height() as f32,
+    }
+}
+
+fn fmt_area(area: &Area) -> String {
+    format!("({}, {}, {}x{})", area.x, area.y, area.width, area.height)
+}
+
+fn json_area(area: &Area) -> String {
+    format!(
+        "{{\"x\": {}, \"y\": {}, \"width\": {}, \"height\": {}}}",
+        json_number(area.x),
+        json_number(area.y),
+        json_number(area.width),
+        json_number(area.height)
+    )
+}
+
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppCtx, Layout, Point, TRANSPARENT, TestApp, clipping, rect, stack, transform};
+    use vello_svg::vello::kurbo::BezPath;
+
+    fn area(x: f32, y: f32, width: f32, height: f32) -> Area {
+        Area {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn node(
+        id: u64,
+        kind: ViewKind,
+        area: Area,
+        text: Option<&str>,
+        clips: Vec<usize>,
+    ) -> ViewNode {
+        ViewNode {
+            id,
+            kind,
+            area,
+            text: text.map(str::to_string),
+            clips,
+            interactions: Vec::new(),
+        }
+    }
+
+    fn whole(area: Area) -> BezPath {
+        Rect::new(
+            area.x as f64,
+            area.y as f64,
+            (area.x + area.width) as f64,
+            (area.y + area.height) as f64,
+        )
+        .to_path(0.1)
+    }
+
+    fn left_half(area: Area) -> BezPath {
+        whole(Area {
+            width: area.width / 2.,
+            ..area
+        })
+    }
+
+    fn fill<'a>(app: &mut AppState) -> Layout<'a, View<()>, AppCtx> {
+        rect(crate::id!())
+            .fill(TRANSPARENT)
+            .view()
+            .finish(app.ctx())
+    }
+
+    fn nested_clips<'a>(_state: &'a (), app: &mut AppState) -> Layout<'a, View<()>, AppCtx> {
+        stack(vec![
+            clipping(
+                whole,
+                stack(vec![fill(app), clipping(left_half, fill(app))]),
+            )
+            .pad(10.),
+            fill(app),
+        ])
+    }
+
+    fn transformed<'a>(_state: &'a (), app: &mut AppState) -> Layout<'a, View<()>, AppCtx> {
+        transform(
+            Affine::translate((10., 0.)) * Affine::scale(0.5),
+            Point::new(0.5, 0.5),
+            clipping(left_half, fill(app)).pad(50.),
+        )
+    }
+
+    #[test]
+    fn find_text_matches_whole_text_views() {
+        let snapshot = FrameSnapshot {
+            nodes: vec![
+                node(1, ViewKind::Path, area(0., 0., 10., 10.), None, vec![]),
+                node(
+                    2,
+                    ViewKind::Text,
+                    area(0., 0., 10., 10.),
+                    Some("Save all"),
+                    vec![],
+                ),
+                node(
+                    3,
+                    ViewKind::Text,
+                    area(0., 0., 10., 10.),
+                    Some("Save"),
+                    vec![],
+                ),
+            ],
+            clips: Vec::new(),
+        };
+        assert_eq!(snapshot.find_text("Save").map(|node| node.id), Some(3));
+        assert_eq!(snapshot.find_text("Save all").map(|node| node.id), Some(2));
+        assert!(snapshot.find_text("Sav").is_none());
+    }
+
+    #[test]
+    fn clips_are_indexed_outermost_first() {
+        let mut app = TestApp::new((), nested_clips).size(200., 100.);
+        let snapshot = app.snapshot();
+        assert_eq!(
+            snapshot.clips,
+            vec![area(10., 10., 180., 80.), area(10., 10., 90., 80.)]
+        );
+        let clips: Vec<_> = snapshot
+            .nodes
+            .iter()
+            .map(|node| node.clips.clone())
+            .collect();
+        assert_eq!(clips, vec![vec![0], vec![0, 1], vec![]]);
+    }
+
+    #[test]
+    fn transformed_areas_are_bounding_boxes() {
+        let mut app = TestApp::new((), transformed).size(200., 200.);
+        let snapshot = app.snapshot();
+        // Scaled by half about the center of the window, then moved right by 10
+        assert_eq!(snapshot.nodes[0].area, area(85., 75., 50., 50.));
+        assert_eq!(snapshot.clips, vec![area(85., 75., 25., 50.)]);
+    }
+
+    #[test]
+    fn display_and_json_are_stable() {
+        let mut clicked = node(1, ViewKind::Path, area(0., 0., 100., 50.), None, vec![0]);
+        clicked.interactions = vec!["click"];
+        let snapshot = FrameSnapshot {
+            nodes: vec![
+                clicked,
+                node(
+                    2,
+                    ViewKind::Text,
+                    area(10., 10., f32::INFINITY, f32::NAN),
+                    Some("say \"hi\"\n\u{1}"),
+                    vec![0, 1],
+                ),
+                node(3, ViewKind::Svg, area(0., 60., 20., 20.), None, vec![]),
+            ],
+            clips: vec![area(0., 0., 100., 50.), area(0., 0., 50., 50.)],
+        };
+        assert_eq!(
+            snapshot.to_string(),
+            concat!(
+                "clip (0, 0, 100x50)\n",
+                "  path #1 (0, 0, 100x50) [click]\n",
+                "  clip (0, 0, 50x50)\n",
+                "    text #2 (10, 10, infxNaN) \"say \\\"hi\\\"\\n\\u{1}\"\n",
+                "svg #3 (0, 60, 20x20)\n",
+            )
+        );
+        assert_eq!(
+            snapshot.to_json(),
+            concat!(
+                "{\n",
+                "  \"clips\": [\n",
+                "    {\"x\": 0, \"y\": 0, \"width\": 100, \"height\": 50},\n",
+                "    {\"x\": 0, \"y\": 0, \"width\": 50, \"height\": 50}\n",
+                "  ],\n",
+                "  \"nodes\": [\n",
+                "    {\"id\": \"1\", \"kind\": \"path\", \"area\": {\"x\": 0, \"y\": 0, \"width\": 100, \"height\": 50}, \"text\": null, \"clips\": [0], \"interactions\": [\"click\"]},\n",
+                "    {\"id\": \"2\", \"kind\": \"text\", \"area\": {\"x\": 10, \"y\": 10, \"width\": null, \"height\": null}, \"text\": \"say \\\"hi\\\"\\n\\u0001\", \"clips\": [0, 1], \"interactions\": []},\n",
+                "    {\"id\": \"3\", \"kind\": \"svg\", \"area\": {\"x\": 0, \"y\": 60, \"width\": 20, \"height\": 20}, \"text\": null, \"clips\": [], \"interactions\": []}\n",
+                "  ]\n",
+                "}\n",
+            )
+        );
+        let empty = FrameSnapshot::default();
+        assert_eq!(empty.to_string(), "");
+        assert_eq!(empty.to_json(), "{\n  \"clips\": [],\n  \"nodes\": []\n}\n");
+    }
+}
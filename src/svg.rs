@@ -45,6 +45,32 @@ impl Svg {
 }
 
 impl Svg {
+    /// Returns the transform that places an svg of `width` x `height` into `area`.
+    pub(crate) fn transform(
+        &self,
+        area: Area,
+        width: f64,
+        height: f64,
+        scale_factor: f64,
+    ) -> Affine {
+        let area_x = area.x as f64 * scale_factor;
+        let area_y = area.y as f64 * scale_factor;
+        let area_width = area.width as f64 * scale_factor;
+        let area_height = area.height as f64 * scale_factor;
+        if self.unlocked_aspect_ratio {
+            Affine::IDENTITY
+                .then_scale_non_uniform(area_width / width, area_height / height)
+                .then_translate(Vec2::new(area_x, area_y))
+        } else {
+            let scale = (area_width / width).min(area_height / height);
+            let dx = area_x + (area_width - width * scale) / 2.0;
+            let dy = area_y + (area_height - height * scale) / 2.0;
+            Affine::IDENTITY
+                .then_scale(scale)
+                .then_translate(Vec2::new(dx, dy))
+        }
+    }
+
     pub(crate) fn draw(&mut self, area: Area, scene: &mut Scene, app: &mut AppState) {
         if !app.svg_scenes.contains_key(&self.content) {
             match vello_svg::usvg::Tree::from_data(
@@ -91,18 +117,7 @@ impl Svg {
             }
             scene.append(
                 svg_scene,
-                Some(if self.unlocked_aspect_ratio {
-                    Affine::IDENTITY
-                        .then_scale_non_uniform(area_width / width, area_height / height)
-                        .then_translate(Vec2::new(area_x, area_y))
-                } else {
-                    let scale = (area_width / width).min(area_height / height);
-                    let dx = area_x + (area_width - width * scale) / 2.0;
-                    let dy = area_y + (area_height - height * scale) / 2.0;
-                    Affine::IDENTITY
-                        .then_scale(scale)
-                        .then_translate(Vec2::new(dx, dy))
-                }),
+                Some(self.transform(area, width, height, app.app_context.scale_factor)),
             );
             if let Some(ref fill) = self.fill {
                 scene.push_layer(
@@ -0,0 +1,451 @@
+use crate::app::{AppEvent, AppState, EventSender, FontEntry, View, ViewFn};
+use crate::event::TouchEvent;
+use crate::input::{InputState, Placement};
+use crate::timers::Clock;
+use crate::{CpuRenderer, FrameSnapshot, Key, KeyEvent, MouseButton, Point};
+use backer::Area;
+use image::RgbaImage;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use winit::event::{ElementState, MouseScrollDelta, TouchPhase};
+use winit::keyboard::{ModifiersState, NamedKey, NativeKeyCode, PhysicalKey};
+use winit::window::CursorIcon;
+
+/// Drives a view function without a window so interactions can be tested.
+///
+/// Events are injected in logical coordinates and delivered to the gesture handlers
+/// of the most recent frame, the same way `App` delivers winit events. The view is
+/// laid out again whenever an event requests a redraw.
+pub struct TestApp<State> {
+    pub state: State,
+    app_state: AppState,
+    view: ViewFn<State>,
+    input: InputState<State>,
+    width: f32,
+    height: f32,
+    views: Vec<View<State>>,
+    events: Receiver<AppEvent>,
+    redraw: tokio::sync::mpsc::Receiver<()>,
+    laid_out: bool,
+    /// Animations read by the last frame.
+    animations: HashSet<u64>,
+}
+
+impl<State: 'static> TestApp<State> {
+    pub fn new(state: State, view: ViewFn<State>) -> Self {
+        Self::with_fonts(state, view, Vec::new())
+    }
+
+    /// Like `new`, additionally registering fonts as `AppBuilder::add_font_bytes` does.
+    pub fn with_fonts(
+        state: State,
+        view: ViewFn<State>,
+        fonts: Vec<(Vec<u8>, Option<&str>)>,
+    ) -> Self {
+        let (event_sender, events) = std::sync::mpsc::channel();
+        let (redraw_sender, redraw) = tokio::sync::mpsc::channel::<()>(10);
+        let fonts: Vec<FontEntry> = fonts
+            .into_iter()
+            .map(|(bytes, family)| (Arc::new(bytes), family.map(|s| s.to_string())))
+            .collect();
+        let mut app_state = AppState::new(
+            Runtime::new().expect("Failed to create runtime"),
+            EventSender::Queue(event_sender),
+            redraw_sender,
+            fonts,
+        );
+        app_state.clock = Clock::virtual_at(app_state.app_context.frame_time);
+        Self {
+            state,
+            app_state,
+            view,
+            input: InputState::default(),
+            width: 1044.,
+            height: 800.,
+            views: Vec::new(),
+            events,
+            redraw,
+            laid_out: false,
+            animations: HashSet::new(),
+        }
+    }
+
+    /// Sets the logical size the view is laid out at.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self.laid_out = false;
+        self
+    }
+
+    pub fn scale_factor(mut self, scale_factor: f64) -> Self {
+        self.app_state.app_context.scale_factor = scale_factor;
+        self.app_state.layout_cache.clear();
+        self.laid_out = false;
+        self
+    }
+
+    pub fn app_state(&mut self) -> &mut AppState {
+        &mut self.app_state
+    }
+
+    /// The draw list of the most recent frame.
+    pub fn views(&mut self) -> &[View<State>] {
+        self.ensure_laid_out();
+        &self.views
+    }
+
+    /// A queryable copy of the most recent frame.
+    pub fn snapshot(&mut self) -> FrameSnapshot {
+        self.ensure_laid_out();
+        FrameSnapshot::capture(&self.views, &self.app_state)
+    }
+
+    /// Lays the view out again and collects its gesture handlers.
+    pub fn layout(&mut self) {
+        self.laid_out = true;
+        self.input.gesture_handlers.clear();
+        let now = self.app_state.clock.now();
+        self.app_state.app_context.begin_frame(now);
+        let view = self.view;
+        let mut draw_items = {
+            let mut layout = view(&self.state, &mut self.app_state);
+            layout.draw(
+                Area {
+                    x: 0.,
+                    y: 0.,
+                    width: self.width,
+                    height: self.height,
+                },
+                &mut self.app_state.app_context,
+            )
+        };
+        draw_items.extend(
+            self.input
+                .drag_preview(&self.state, &mut self.app_state.app_context),
+        );
+        self.app_state
+            .app_context
+            .prune_animations(&mut self.animations);
+        let mut clip_stack = Vec::new();
+        let mut transform_stack = Vec::new();
+        let mut placement = Placement::default();
+        for item in &draw_items {
+            match item {
+                View::PushClip { path } => {
+                    clip_stack.push(placement.transform * path.clone());
+                    placement.clips = clip_stack.as_slice().into();
+                }
+                View::PopClip => {
+                    clip_stack.pop();
+                    placement.clips = clip_stack.as_slice().into();
+                }
+                View::PushTransform { transform } => {
+                    transform_stack.push(placement.transform);
+                    placement.transform *= *transform;
+                }
+                View::PopTransform => {
+                    placement.transform = transform_stack.pop().unwrap_or_default();
+                }
+                View::PushLayer { .. } | View::PopLayer => (),
+                View::EditorArea(id, area) => {
+                    self.app_state
+                        .app_context
+                        .editor_areas
+                        .insert(*id, (*area, placement.clone()));
+                }
+                View::Draw {
+                    view,
+                    gesture_handlers,
+                    area,
+                } => {
+                    let id = view.id();
+                    self.input.gesture_handlers.extend(
+                        gesture_handlers
+                            .iter()
+                            .map(|h| (id, *area, h.clone(), placement.clone())),
+                    );
+                }
+                View::Empty => (),
+            }
+        }
+        self.views = draw_items;
+        if self.input.sync_focus(&mut self.state, &mut self.app_state) {
+            self.layout();
+        }
+    }
+
+    /// Renders the most recent frame with `renderer` at the physical size of the view.
+    pub fn render(&mut self, renderer: &mut CpuRenderer) -> RgbaImage {
+        self.ensure_laid_out();
+        let scale_factor = self.app_state.app_context.scale_factor;
+        renderer.render(
+            &self.views,
+            &mut self.app_state,
+            (self.width as f64 * scale_factor).round() as u32,
+            (self.height as f64 * scale_factor).round() as u32,
+        )
+    }
+
+    /// The cursor icon the window would show at the current pointer position.
+    pub fn cursor(&self) -> CursorIcon {
+        self.input.cursor
+    }
+
+    /// Sets the modifier keys held for subsequent events.
+    pub fn modifiers(&mut self, modifiers: ModifiersState) {
+        self.app_state.modifiers = Some(modifiers.into());
+    }
+
+    pub fn move_pointer(&mut self, x: f64, y: f64) {
+        self.ensure_laid_out();
+        let needs_redraw =
+            self.input
+                .mouse_moved(&mut self.state, &mut self.app_state, Point::new(x, y));
+        self.finish_event(needs_redraw);
+    }
+
+    pub fn press(&mut self) {
+        self.press_button(MouseButton::Left);
+    }
+
+    pub fn release(&mut self) {
+        self.release_button(MouseButton::Left);
+    }
+
+    pub fn press_button(&mut self, button: MouseButton) {
+        self.ensure_laid_out();
+        let needs_redraw = self
+            .input
+            .mouse_pressed(&mut self.state, &mut self.app_state, button);
+        self.finish_event(needs_redraw);
+    }
+
+    pub fn release_button(&mut self, button: MouseButton) {
+        self.ensure_laid_out();
+        let needs_redraw = self
+            .input
+            .mouse_released(&mut self.state, &mut self.app_state, button);
+        self.finish_event(needs_redraw);
+    }
+
+    /// Moves the pointer to `(x, y)`, then presses and releases.
+    pub fn click(&mut self, x: f64, y: f64) {
+        self.click_button(x, y, MouseButton::Left);
+    }
+
+    pub fn click_button(&mut self, x: f64, y: f64, button: MouseButton) {
+        self.move_pointer(x, y);
+        self.press_button(button);
+        self.release_button(button);
+    }
+
+    /// Presses at `from`, moves to `to` in `steps` increments and releases.
+    pub fn drag(&mut self, from: (f64, f64), to: (f64, f64), steps: u32) {
+        self.move_pointer(from.0, from.1);
+        self.press();
+        let steps = steps.max(1);
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            self.move_pointer(from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+        }
+        self.release();
+    }
+
+    pub fn leave(&mut self) {
+        self.ensure_laid_out();
+        let needs_redraw = self
+            .input
+            .mouse_exited(&mut self.state, &mut self.app_state);
+        self.finish_event(needs_redraw);
+    }
+
+    /// Presses and releases `key`.
+    pub fn key(&mut self, key: Key) {
+        self.key_down(key.clone());
+        self.key_up(key);
+    }
+
+    pub fn key_down(&mut self, key: Key) {
+        self.key_input(key, ElementState::Pressed);
+    }
+
+    pub fn key_up(&mut self, key: Key) {
+        self.key_input(key, ElementState::Released);
+    }
+
+    fn key_input(&mut self, key: Key, element_state: ElementState) {
+        self.ensure_laid_out();
+        let event = KeyEvent {
+            key,
+            physical_key: PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+            state: element_state,
+            is_repeat: false,
+            modifiers: self
+                .app_state
+                .modifiers
+                .map(|modifiers| modifiers.state())
+                .unwrap_or_default(),
+        };
+        let needs_redraw = self
+            .input
+            .key_input(&mut self.state, &mut self.app_state, event);
+        self.finish_event(needs_redraw);
+    }
+
+    /// Presses a key for each character of `text`.
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.key(match c {
+                ' ' => Key::Named(NamedKey::Space),
+                '\n' => Key::Named(NamedKey::Enter),
+                c => Key::Character(c.to_string().into()),
+            });
+        }
+    }
+
+    /// Scrolls by a delta in logical pixels at the current pointer position.
+    pub fn scroll(&mut self, dx: f64, dy: f64) {
+        self.scroll_with(
+            MouseScrollDelta::PixelDelta(winit::dpi::PhysicalPosition::new(dx, dy)),
+            TouchPhase::Moved,
+        );
+    }
+
+    /// Scrolls by whole mouse wheel notches at the current pointer position.
+    pub fn scroll_lines(&mut self, dx: f32, dy: f32) {
+        self.scroll_with(MouseScrollDelta::LineDelta(dx, dy), TouchPhase::Moved);
+    }
+
+    /// Scrolls by a delta in logical pixels as part of a trackpad gesture. Start with `TouchPhase::Started`
+    /// and lift the fingers with `Ended`.
+    pub fn trackpad_scroll(&mut self, dx: f64, dy: f64, phase: TouchPhase) {
+        self.scroll_with(
+            MouseScrollDelta::PixelDelta(winit::dpi::PhysicalPosition::new(dx, dy)),
+            phase,
+        );
+    }
+
+    fn scroll_with(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {
+        self.ensure_laid_out();
+        let needs_redraw = self
+            .input
+            .scrolled(&mut self.state, &mut self.app_state, delta, phase);
+        self.finish_event(needs_redraw);
+    }
+
+    /// Moves the touch point `id` to `(x, y)`. Use `TouchPhase::Started` for the first
+    /// event of a finger and `Ended` when it is lifted.
+    pub fn touch(&mut self, id: u64, phase: TouchPhase, x: f64, y: f64) {
+        self.ensure_laid_out();
+        let needs_redraw = self.input.touch(
+            &mut self.state,
+            &mut self.app_state,
+            TouchEvent {
+                id,
+                phase,
+                position: Point::new(x, y),
+            },
+        );
+        self.finish_event(needs_redraw);
+    }
+
+    /// A trackpad pinch at the current pointer position.
+    pub fn trackpad_pinch(&mut self, delta: f64, phase: TouchPhase) {
+        self.ensure_laid_out();
+        let needs_redraw =
+            self.input
+                .trackpad_pinch(&mut self.state, &mut self.app_state, delta, phase);
+        self.finish_event(needs_redraw);
+    }
+
+    /// A trackpad rotation at the current pointer position, in degrees counterclockwise.
+    pub fn trackpad_rotate(&mut self, delta: f32, phase: TouchPhase) {
+        self.ensure_laid_out();
+        let needs_redraw =
+            self.input
+                .trackpad_rotate(&mut self.state, &mut self.app_state, delta, phase);
+        self.finish_event(needs_redraw);
+    }
+
+    /// A two-finger trackpad pan at the current pointer position.
+    pub fn trackpad_pan(&mut self, dx: f64, dy: f64, phase: TouchPhase) {
+        self.ensure_laid_out();
+        let needs_redraw = self.input.trackpad_pan(
+            &mut self.state,
+            &mut self.app_state,
+            Point::new(dx, dy),
+            phase,
+        );
+        self.finish_event(needs_redraw);
+    }
+
+    /// Moves the pointer to `(x, y)` while dragging `paths` from the OS over the window.
+    pub fn hover_files(&mut self, paths: Vec<PathBuf>, x: f64, y: f64) {
+        self.move_pointer(x, y);
+        let needs_redraw = self
+            .input
+            .files_hovered(&mut self.state, &mut self.app_state, paths);
+        self.finish_event(needs_redraw);
+    }
+
+    /// Drops `paths` from the OS at `(x, y)`.
+    pub fn drop_files(&mut self, paths: Vec<PathBuf>, x: f64, y: f64) {
+        self.move_pointer(x, y);
+        let needs_redraw = self
+            .input
+            .files_dropped(&mut self.state, &mut self.app_state, paths);
+        self.finish_event(needs_redraw);
+    }
+
+    pub fn cancel_file_hover(&mut self) {
+        self.ensure_laid_out();
+        let needs_redraw = self
+            .input
+            .file_hover_cancelled(&mut self.state, &mut self.app_state);
+        self.finish_event(needs_redraw);
+    }
+
+    /// Moves time forward by `by`, running the timers and firing the long presses and hover
+    /// intents that become due. If the last frame asked for another, e.g. during a scroll
+    /// fling or an animation, it is laid out again.
+    pub fn advance(&mut self, by: Duration) {
+        self.ensure_laid_out();
+        self.app_state.clock.advance(by);
+        let ran_timers = self.app_state.run_timers(&mut self.state);
+        let needs_redraw = self.input.fire_timers(&mut self.state, &mut self.app_state)
+            || ran_timers
+            || self.app_state.app_context.frame_requested;
+        self.finish_event(needs_redraw);
+    }
+
+    fn ensure_laid_out(&mut self) {
+        if !self.laid_out {
+            self.layout();
+        }
+    }
+
+    /// Runs queued callbacks and lays out again if anything asked for a redraw.
+    fn finish_event(&mut self, mut needs_redraw: bool) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                AppEvent::Callback(cb) => {
+                    cb(&mut self.state);
+                    needs_redraw = true;
+                }
+                AppEvent::RequestRedraw => needs_redraw = true,
+                AppEvent::OpenWindow(_) | AppEvent::CloseWindow(_) => (),
+            }
+        }
+        while self.redraw.try_recv().is_ok() {
+            needs_redraw = true;
+        }
+        if needs_redraw {
+            self.layout();
+        }
+    }
+}
@@ -206,6 +206,16 @@ impl Text {
         scene: &mut Scene,
         app: &mut AppState,
     ) {
+        let (fill, layout, transform) = self.prepare(animated_area, area, app);
+        draw_layout(Some(fill), transform, &layout, scene);
+    }
+
+    pub(crate) fn prepare(
+        &self,
+        animated_area: Area,
+        area: Area,
+        app: &mut AppState,
+    ) -> (Brush, ParleyLayout<Brush>, Affine) {
         let fill = self.fill.resolve(area, &());
 
         let layout = app
@@ -216,7 +226,7 @@ impl Text {
         let transform = Affine::translate((animated_area.x as f64, animated_area.y as f64))
             .then_scale(app.app_context.scale_factor);
 
-        draw_layout(Some(fill), transform, &layout, scene);
+        (fill, layout, transform)
     }
 }
 
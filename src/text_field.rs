@@ -1,6 +1,6 @@
 use crate::app::{AppCtx, AppState, EditState, View};
 use crate::background_style::BrushSource;
-use crate::shape::{PathData, rect_path};
+use crate::shape::{PathData, ShadowShape, rect_path};
 use crate::view::DrawableType;
 use crate::{
     Binding, DEFAULT_CORNER_ROUNDING, DEFAULT_FG_COLOR, DEFAULT_FONT_FAMILY, DEFAULT_FONT_SIZE,
@@ -13,6 +13,7 @@ use std::rc::Rc;
 use vello_svg::vello::kurbo::{Affine, Rect as KRect, Stroke};
 use vello_svg::vello::peniko::color::palette::css::TRANSPARENT;
 use vello_svg::vello::peniko::{Brush, Color};
+use winit::window::CursorIcon;
 
 #[derive(Debug, Clone, Default)]
 pub struct TextState {
@@ -249,6 +250,9 @@ impl<'a, State> TextField<'a, State> {
                             builder: rect_path((2., 2., 2., 2.)),
                             fill: Some(highlight.resolve(resolved_area, &ts).into()),
                             stroke: None,
+                            shadows: Vec::new(),
+                            shadow_shape: ShadowShape::Path,
+                            backdrop_blur: None,
                         }))),
                         gesture_handlers: Vec::new(),
                         area: resolved_area,
@@ -290,6 +294,9 @@ impl<'a, State> TextField<'a, State> {
                             builder: rect_path((rounding, rounding, rounding, rounding)),
                             fill: Some(cursor_fill.resolve(resolved_area, &ts).into()),
                             stroke: None,
+                            shadows: Vec::new(),
+                            shadow_shape: ShadowShape::Path,
+                            backdrop_blur: None,
                         }))),
                         gesture_handlers: Vec::new(),
                         area: resolved_area,
@@ -346,15 +353,80 @@ impl<'a, State> TextField<'a, State> {
                 let binding = binding.clone();
                 let font_family = self.font_family.clone();
                 let on_edit = self.on_edit.clone();
+                let end_editing: Rc<dyn Fn(&mut State, &mut AppState)> = {
+                    let binding = binding.clone();
+                    let on_edit = on_edit.clone();
+                    Rc::new(move |state, app| {
+                        if let AppState {
+                            app_context:
+                                AppCtx {
+                                    editor: Some(EditState { id, .. }),
+                                    ..
+                                },
+                            ..
+                        } = app
+                            && *id == root_id
+                        {
+                            app.end_editing();
+                            binding.update(state, |s| s.editing = false);
+                            if let Some(ref on_edit) = on_edit {
+                                (on_edit)(state, app, EditInteraction::End);
+                            }
+                        }
+                    })
+                };
+                let begin_editing: Rc<dyn Fn(&mut State, &mut AppState)> = {
+                    let binding = binding.clone();
+                    Rc::new(move |state, app| {
+                        let editing = binding.get(state).editing;
+                        if !editing && app.app_context.editor.is_none() {
+                            binding.update(state, |s| s.editing = true);
+                            let editor_area = app
+                                .app_context
+                                .editor_areas
+                                .get(&root_id)
+                                .map(|(area, _)| *area)
+                                .unwrap_or(Area {
+                                    x: 0.,
+                                    y: 0.,
+                                    width: 0.,
+                                    height: 0.,
+                                });
+                            let ts = binding.get(state);
+                            let text = ts.text.clone();
+                            app.begin_editing(
+                                root_id,
+                                text,
+                                self.text_fill.resolve(editor_area, &ts),
+                                font_family
+                                    .clone()
+                                    .unwrap_or(DEFAULT_FONT_FAMILY.to_string()),
+                                self.font_weight,
+                                self.line_height,
+                                self.font_size as f32,
+                                parley::OverflowWrap::Anywhere,
+                                self.alignment,
+                                self.cursor_fill.resolve(editor_area, &ts),
+                                self.highlight_fill.resolve(editor_area, &ts),
+                                self.wrap,
+                            );
+                        }
+                    })
+                };
                 stack(vec![
                     draw(move |area, _| vec![View::EditorArea(root_id, area)]),
                     rect(root_id)
                         .fill(TRANSPARENT)
                         .view()
+                        .cursor(CursorIcon::Text)
                         .on_key({
                             let on_edit = on_edit.clone();
                             let binding = binding.clone();
-                            move |state, app, key| {
+                            move |state, app, event| {
+                                if !event.is_pressed() {
+                                    return;
+                                }
+                                let key = event.key;
                                 if (self.enter_end_editing
                                     && key == Key::Named(winit::keyboard::NamedKey::Enter))
                                     || (self.esc_end_editing
@@ -380,7 +452,7 @@ impl<'a, State> TextField<'a, State> {
                                 } = app
                                     && *id == root_id
                                 {
-                                    editor.handle_key(key.clone(), layout_cx, font_cx, *modifiers);
+                                    editor.handle_key(key, layout_cx, font_cx, *modifiers);
                                 }
                                 let edit_text = app
                                     .app_context
@@ -406,64 +478,18 @@ impl<'a, State> TextField<'a, State> {
                             }
                         })
                         .on_click_outside({
-                            let binding = binding.clone();
-                            let on_edit = on_edit.clone();
-                            move |state: &mut State, app, _, _| {
-                                if let AppState {
-                                    app_context:
-                                        AppCtx {
-                                            editor: Some(EditState { id, .. }),
-                                            ..
-                                        },
-                                    ..
-                                } = app
-                                    && *id == root_id
-                                {
-                                    app.end_editing();
-                                    binding.update(state, |s| s.editing = false);
-                                    if let Some(ref on_edit) = on_edit {
-                                        (on_edit)(state, app, EditInteraction::End);
-                                    }
-                                }
-                            }
+                            let end_editing = end_editing.clone();
+                            move |state: &mut State, app, _, _| end_editing(state, app)
                         })
                         .on_click({
-                            let binding = binding.clone();
-                            let font_family = font_family.clone();
-                            move |state: &mut State, app, _, _| {
-                                let editing = binding.get(state).editing;
-                                if !editing && app.app_context.editor.is_none() {
-                                    binding.update(state, |s| s.editing = true);
-                                    let editor_area = app
-                                        .app_context
-                                        .editor_areas
-                                        .get(&root_id)
-                                        .copied()
-                                        .unwrap_or(Area {
-                                            x: 0.,
-                                            y: 0.,
-                                            width: 0.,
-                                            height: 0.,
-                                        });
-                                    let ts = binding.get(state);
-                                    let text = ts.text.clone();
-                                    app.begin_editing(
-                                        root_id,
-                                        text,
-                                        self.text_fill.resolve(editor_area, &ts),
-                                        font_family
-                                            .clone()
-                                            .unwrap_or(DEFAULT_FONT_FAMILY.to_string()),
-                                        self.font_weight,
-                                        self.line_height,
-                                        self.font_size as f32,
-                                        parley::OverflowWrap::Anywhere,
-                                        self.alignment,
-                                        self.cursor_fill.resolve(editor_area, &ts),
-                                        self.highlight_fill.resolve(editor_area, &ts),
-                                        self.wrap,
-                                    );
-                                }
+                            let begin_editing = begin_editing.clone();
+                            move |state: &mut State, app, _, _| begin_editing(state, app)
+                        })
+                        .on_focus(move |state: &mut State, app, focused| {
+                            if focused {
+                                begin_editing(state, app)
+                            } else {
+                                end_editing(state, app)
                             }
                         })
                         .finish(ctx),
@@ -495,3 +521,93 @@ impl<'a, State> TextField<'a, State> {
         stack(vec![bg.inert(), padded_content])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Clone, Default)]
+    struct State {
+        field: TextState,
+        ended: bool,
+    }
+
+    fn view<'a>(state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        column(vec![
+            text_field(crate::id!(), crate::binding!(state, State, field))
+                .enter_end_editing()
+                .on_edit(|state: &mut State, _app, edit| {
+                    if let EditInteraction::End = edit {
+                        state.ended = true;
+                    }
+                })
+                .build(app.ctx())
+                .height(40.),
+            space(),
+        ])
+    }
+
+    #[test]
+    fn click_and_type_edits_text() {
+        let mut app = TestApp::new(
+            State {
+                field: TextState::new("ab"),
+                ended: false,
+            },
+            view,
+        )
+        .size(200., 100.);
+        app.click(100., 20.);
+        assert!(app.state.field.editing);
+        app.key(Key::Named(NamedKey::End));
+        app.type_text("c d");
+        assert_eq!(app.state.field.text, "abc d");
+        app.key(Key::Named(NamedKey::Backspace));
+        app.key(Key::Named(NamedKey::Home));
+        app.type_text("x");
+        assert_eq!(app.state.field.text, "xabc ");
+        app.key(Key::Named(NamedKey::Enter));
+        assert!(!app.state.field.editing);
+        assert!(app.state.ended);
+    }
+
+    #[test]
+    fn click_outside_ends_editing() {
+        let mut app = TestApp::new(State::default(), view).size(200., 100.);
+        app.click(100., 20.);
+        assert!(app.state.field.editing);
+        app.type_text("hi");
+        app.click(100., 80.);
+        assert!(!app.state.field.editing);
+        assert_eq!(app.state.field.text, "hi");
+    }
+
+    fn transformed_view<'a>(
+        state: &'a State,
+        app: &mut AppState,
+    ) -> Layout<'a, View<State>, AppCtx> {
+        row(vec![
+            transform(
+                Affine::translate((100., 0.)),
+                Point::ZERO,
+                text_field(crate::id!(), crate::binding!(state, State, field))
+                    .build(app.ctx())
+                    .width(100.),
+            ),
+            space(),
+        ])
+    }
+
+    #[test]
+    fn editing_follows_the_transform() {
+        let mut app = TestApp::new(State::default(), transformed_view).size(300., 40.);
+        assert!(app.snapshot().nodes.iter().all(|node| node.area.x >= 100.));
+        // Inside the field as drawn, outside where it was laid out
+        app.click(150., 20.);
+        assert!(app.state.field.editing);
+        app.type_text("hi");
+        assert_eq!(app.state.field.text, "hi");
+        app.click(50., 20.);
+        assert!(!app.state.field.editing);
+    }
+}
@@ -0,0 +1,511 @@
+use crate::app::AppState;
+use crate::gestures::{ClickLocation, Interaction};
+use crate::input::InputState;
+use crate::{GestureState, MouseButton, Point};
+use std::any::Any;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// How far the pointer may wander before a long press is cancelled or a hover intent restarts.
+const HOLD_DISTANCE: f64 = 6.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TimedGesture {
+    LongPress,
+    HoverIntent,
+}
+
+/// A gesture waiting for the pointer to stay put until `deadline`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GestureTimer {
+    kind: TimedGesture,
+    id: u64,
+    delay: Duration,
+    /// Where the pointer was when the timer started.
+    origin: Point,
+    deadline: Instant,
+}
+
+/// The time source for timers and frame times. `TestApp` moves it forward instead of sleeping.
+#[derive(Debug, Default)]
+pub(crate) struct Clock {
+    /// Set for a virtual clock, which only moves when advanced. The event loop reads wall time.
+    base: Option<Instant>,
+    offset: Duration,
+}
+
+impl Clock {
+    /// A clock that stands still at `base` until it's advanced.
+    pub(crate) fn virtual_at(base: Instant) -> Self {
+        Self {
+            base: Some(base),
+            offset: Duration::ZERO,
+        }
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        self.base.unwrap_or_else(Instant::now) + self.offset
+    }
+
+    pub(crate) fn advance(&mut self, by: Duration) {
+        self.offset += by;
+    }
+}
+
+/// Identifies a timer started with `AppState::set_timeout` or `AppState::set_interval`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+/// A callback waiting for its deadline, run on the UI thread.
+#[derive(Clone)]
+pub(crate) struct AppTimer {
+    id: TimerId,
+    deadline: Instant,
+    /// The period of an interval. Timeouts are removed once they run.
+    interval: Option<Duration>,
+    handler: Rc<dyn Fn(&mut dyn Any, &mut AppState)>,
+}
+
+impl AppState {
+    /// Runs `f` once after `delay`.
+    pub fn set_timeout<State: 'static>(
+        &mut self,
+        delay: Duration,
+        f: impl Fn(&mut State, &mut AppState) + 'static,
+    ) -> TimerId {
+        self.add_timer(delay, None, f)
+    }
+
+    /// Runs `f` every `period` until the timer is cleared. Periods missed while the app was
+    /// busy are skipped rather than run back to back.
+    pub fn set_interval<State: 'static>(
+        &mut self,
+        period: Duration,
+        f: impl Fn(&mut State, &mut AppState) + 'static,
+    ) -> TimerId {
+        self.add_timer(period, Some(period), f)
+    }
+
+    pub fn clear_timer(&mut self, id: TimerId) {
+        self.timers.retain(|timer| timer.id != id);
+    }
+
+    /// Draws another frame after the current one, or soon when called outside a frame.
+    /// Call it from every frame for continuous rendering.
+    pub fn request_frame(&mut self) {
+        self.app_context.frame_requested = true;
+    }
+
+    fn add_timer<State: 'static>(
+        &mut self,
+        delay: Duration,
+        interval: Option<Duration>,
+        f: impl Fn(&mut State, &mut AppState) + 'static,
+    ) -> TimerId {
+        self.next_timer_id += 1;
+        let id = TimerId(self.next_timer_id);
+        self.timers.push(AppTimer {
+            id,
+            deadline: self.clock.now() + delay,
+            interval,
+            handler: Rc::new(move |state, app| {
+                let state = state.downcast_mut::<State>();
+                debug_assert!(
+                    state.is_some(),
+                    "timer callback takes `{}`, which isn't the app's state type",
+                    std::any::type_name::<State>()
+                );
+                if let Some(state) = state {
+                    f(state, app);
+                }
+            }),
+        });
+        id
+    }
+
+    /// The earliest time an app timer is due, for the event loop to wake up at.
+    pub(crate) fn next_timer_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Runs the app timers that are due. Returns whether any ran.
+    pub(crate) fn run_timers(&mut self, state: &mut dyn Any) -> bool {
+        let now = self.clock.now();
+        let due: Vec<AppTimer> = self
+            .timers
+            .iter()
+            .filter(|timer| timer.deadline <= now)
+            .cloned()
+            .collect();
+        let mut ran = false;
+        for timer in due {
+            // An earlier callback may have cleared it
+            let Some(index) = self.timers.iter().position(|t| t.id == timer.id) else {
+                continue;
+            };
+            match timer.interval {
+                Some(period) => {
+                    let next = timer.deadline + period;
+                    self.timers[index].deadline = if next > now { next } else { now + period };
+                }
+                None => {
+                    self.timers.remove(index);
+                }
+            }
+            ran = true;
+            (timer.handler)(state, self);
+        }
+        ran
+    }
+}
+
+impl<State: 'static> InputState<State> {
+    /// Starts the long press timers of the view that captured a press at `point`.
+    pub(crate) fn schedule_long_press(&mut self, capturer: u64, point: Point, now: Instant) {
+        self.cancel_long_press();
+        for (id, _, handler, _) in &self.gesture_handlers {
+            if *id == capturer
+                && let Some(delay) = handler.interaction_type.long_press
+            {
+                self.timers.push(GestureTimer {
+                    kind: TimedGesture::LongPress,
+                    id: *id,
+                    delay,
+                    origin: point,
+                    deadline: now + delay,
+                });
+            }
+        }
+    }
+
+    pub(crate) fn cancel_long_press(&mut self) {
+        self.timers
+            .retain(|timer| timer.kind != TimedGesture::LongPress);
+    }
+
+    /// Cancels long presses the pointer has moved away from and keeps hover intents in step
+    /// with the `hovered` views. `pos` is `None` once the pointer has left the window.
+    pub(crate) fn update_timers(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        pos: Option<Point>,
+        hovered: &[u64],
+    ) -> bool {
+        let now = app_state.clock.now();
+        self.timers.retain_mut(|timer| {
+            let Some(pos) = pos else {
+                return false;
+            };
+            if timer.origin.distance(pos) <= HOLD_DISTANCE {
+                return true;
+            }
+            match timer.kind {
+                TimedGesture::LongPress => false,
+                TimedGesture::HoverIntent => {
+                    timer.origin = pos;
+                    timer.deadline = now + timer.delay;
+                    true
+                }
+            }
+        });
+        self.timers
+            .retain(|timer| timer.kind != TimedGesture::HoverIntent || hovered.contains(&timer.id));
+        let (active, ended): (Vec<_>, Vec<_>) = std::mem::take(&mut self.hover_intents)
+            .into_iter()
+            .partition(|(id, _)| hovered.contains(id));
+        self.hover_intents = active;
+        let mut needs_redraw = false;
+        for (id, delay) in ended {
+            needs_redraw |= self.send_hover_intent(state, app_state, id, delay, false);
+        }
+        let Some(pos) = pos else {
+            return needs_redraw;
+        };
+        for (id, _, handler, _) in &self.gesture_handlers {
+            let Some(delay) = handler.interaction_type.hover_intent else {
+                continue;
+            };
+            let waiting = self.timers.iter().any(|timer| {
+                timer.kind == TimedGesture::HoverIntent && timer.id == *id && timer.delay == delay
+            });
+            if hovered.contains(id) && !waiting && !self.hover_intents.contains(&(*id, delay)) {
+                self.timers.push(GestureTimer {
+                    kind: TimedGesture::HoverIntent,
+                    id: *id,
+                    delay,
+                    origin: pos,
+                    deadline: now + delay,
+                });
+            }
+        }
+        needs_redraw
+    }
+
+    /// The earliest time a gesture timer is due, for the event loop to wake up at.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Delivers the gesture timers that are due.
+    pub(crate) fn fire_timers(&mut self, state: &mut State, app_state: &mut AppState) -> bool {
+        let now = app_state.clock.now();
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.timers)
+            .into_iter()
+            .partition(|timer| timer.deadline <= now);
+        self.timers = pending;
+        let mut needs_redraw = false;
+        for timer in due {
+            match timer.kind {
+                TimedGesture::LongPress => {
+                    needs_redraw |= self.send_long_press(state, app_state, timer);
+                }
+                TimedGesture::HoverIntent => {
+                    self.hover_intents.push((timer.id, timer.delay));
+                    needs_redraw |=
+                        self.send_hover_intent(state, app_state, timer.id, timer.delay, true);
+                }
+            }
+        }
+        needs_redraw
+    }
+
+    fn send_long_press(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        timer: GestureTimer,
+    ) -> bool {
+        // The press may have been released or turned into a drag since the timer started
+        let GestureState::Dragging {
+            capturer,
+            button: MouseButton::Left,
+            ..
+        } = self.gesture_state
+        else {
+            return false;
+        };
+        if capturer != timer.id || self.drag_session.is_some() {
+            return false;
+        }
+        self.long_pressed = true;
+        let mut sent = false;
+        for (id, area, handler, placement) in self.gesture_handlers.clone() {
+            if id == timer.id
+                && handler.interaction_type.long_press == Some(timer.delay)
+                && let Some(ref on_long_press) = handler.interaction_handler
+            {
+                sent = true;
+                on_long_press(
+                    state,
+                    app_state,
+                    Interaction::LongPress(ClickLocation::new(
+                        timer.origin,
+                        placement.local(&area, timer.origin),
+                        MouseButton::Left,
+                        self.click_counter.count,
+                    )),
+                );
+            }
+        }
+        sent
+    }
+
+    fn send_hover_intent(
+        &self,
+        state: &mut State,
+        app_state: &mut AppState,
+        target: u64,
+        delay: Duration,
+        intent: bool,
+    ) -> bool {
+        let mut sent = false;
+        for (id, _, handler, _) in self.gesture_handlers.clone() {
+            if id == target
+                && handler.interaction_type.hover_intent == Some(delay)
+                && let Some(ref on_hover_intent) = handler.interaction_handler
+            {
+                sent = true;
+                on_hover_intent(state, app_state, Interaction::HoverIntent(intent));
+            }
+        }
+        sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HOLD_DISTANCE;
+    use crate::*;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct State {
+        fired: u32,
+    }
+
+    fn view<'a>(_state: &'a State, _app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        space()
+    }
+
+    #[test]
+    fn timeout_runs_once_at_its_deadline() {
+        let mut app = TestApp::new(State::default(), view);
+        app.app_state()
+            .set_timeout(Duration::from_millis(100), |state: &mut State, _app| {
+                state.fired += 1
+            });
+        app.advance(Duration::from_millis(99));
+        assert_eq!(app.state.fired, 0);
+        app.advance(Duration::from_millis(1));
+        assert_eq!(app.state.fired, 1);
+        app.advance(Duration::from_millis(500));
+        assert_eq!(app.state.fired, 1);
+    }
+
+    #[test]
+    fn interval_repeats_until_cleared() {
+        let mut app = TestApp::new(State::default(), view);
+        let id = app
+            .app_state()
+            .set_interval(Duration::from_millis(50), |state: &mut State, _app| {
+                state.fired += 1
+            });
+        for _ in 0..3 {
+            app.advance(Duration::from_millis(50));
+        }
+        assert_eq!(app.state.fired, 3);
+        // Missed periods are skipped rather than run back to back
+        app.advance(Duration::from_millis(500));
+        assert_eq!(app.state.fired, 4);
+        app.app_state().clear_timer(id);
+        app.advance(Duration::from_millis(500));
+        assert_eq!(app.state.fired, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't the app's state type")]
+    #[cfg(debug_assertions)]
+    fn timer_for_another_state_type_panics() {
+        let mut app = TestApp::new(State::default(), view);
+        app.app_state()
+            .set_timeout(Duration::from_millis(10), |_: &mut u32, _app| ());
+        app.advance(Duration::from_millis(10));
+    }
+
+    #[test]
+    fn timers_can_be_cleared_from_a_callback() {
+        let mut app = TestApp::new(State::default(), view);
+        let id = app
+            .app_state()
+            .set_timeout(Duration::from_millis(20), |state: &mut State, _app| {
+                state.fired += 10
+            });
+        app.app_state()
+            .set_timeout(Duration::from_millis(10), move |state: &mut State, app| {
+                state.fired += 1;
+                app.clear_timer(id);
+            });
+        app.advance(Duration::from_millis(10));
+        app.advance(Duration::from_millis(100));
+        assert_eq!(app.state.fired, 1);
+    }
+
+    #[derive(Default)]
+    struct Gestures {
+        long_presses: u32,
+        clicks: u32,
+        intents: Vec<bool>,
+    }
+
+    fn gesture_view<'a>(
+        _state: &'a Gestures,
+        app: &mut AppState,
+    ) -> Layout<'a, View<Gestures>, AppCtx> {
+        row(vec![
+            rect(crate::id!())
+                .fill(TRANSPARENT)
+                .view()
+                .on_long_press(
+                    Duration::from_millis(500),
+                    |state: &mut Gestures, _app, _location| state.long_presses += 1,
+                )
+                .on_click(|state: &mut Gestures, _app, click, _location| {
+                    if click == ClickState::Completed {
+                        state.clicks += 1
+                    }
+                })
+                .finish(app.ctx()),
+            rect(crate::id!())
+                .fill(TRANSPARENT)
+                .view()
+                .on_hover_intent(
+                    Duration::from_millis(300),
+                    |state: &mut Gestures, _app, intent| state.intents.push(intent),
+                )
+                .finish(app.ctx()),
+        ])
+    }
+
+    #[test]
+    fn long_press_fires_after_holding_still() {
+        let mut app = TestApp::new(Gestures::default(), gesture_view).size(200., 100.);
+        app.move_pointer(50., 50.);
+        app.press();
+        app.advance(Duration::from_millis(499));
+        assert_eq!(app.state.long_presses, 0);
+        // Small wobbles don't count as moving away
+        app.move_pointer(50. + HOLD_DISTANCE - 1., 50.);
+        app.advance(Duration::from_millis(1));
+        assert_eq!(app.state.long_presses, 1);
+    }
+
+    #[test]
+    fn moving_past_the_hold_distance_cancels_the_long_press() {
+        let mut app = TestApp::new(Gestures::default(), gesture_view).size(200., 100.);
+        app.move_pointer(50., 50.);
+        app.press();
+        app.advance(Duration::from_millis(200));
+        app.move_pointer(50. + HOLD_DISTANCE + 1., 50.);
+        app.advance(Duration::from_millis(1000));
+        assert_eq!(app.state.long_presses, 0);
+    }
+
+    #[test]
+    fn long_press_suppresses_the_following_click() {
+        let mut app = TestApp::new(Gestures::default(), gesture_view).size(200., 100.);
+        app.move_pointer(50., 50.);
+        app.press();
+        app.advance(Duration::from_millis(500));
+        app.release();
+        assert_eq!(app.state.long_presses, 1);
+        assert_eq!(app.state.clicks, 0);
+        app.advance(Duration::from_secs(1));
+        app.click(50., 50.);
+        assert_eq!(app.state.clicks, 1);
+    }
+
+    #[test]
+    fn hover_intent_fires_after_resting_and_ends_on_leave() {
+        let mut app = TestApp::new(Gestures::default(), gesture_view).size(200., 100.);
+        app.move_pointer(150., 50.);
+        app.advance(Duration::from_millis(299));
+        assert!(app.state.intents.is_empty());
+        app.advance(Duration::from_millis(1));
+        assert_eq!(app.state.intents, vec![true]);
+        app.move_pointer(50., 50.);
+        assert_eq!(app.state.intents, vec![true, false]);
+    }
+
+    #[test]
+    fn moving_restarts_the_hover_intent_delay() {
+        let mut app = TestApp::new(Gestures::default(), gesture_view).size(200., 100.);
+        app.move_pointer(150., 50.);
+        app.advance(Duration::from_millis(200));
+        app.move_pointer(150. + HOLD_DISTANCE + 1., 50.);
+        app.advance(Duration::from_millis(200));
+        assert!(app.state.intents.is_empty());
+        app.advance(Duration::from_millis(100));
+        assert_eq!(app.state.intents, vec![true]);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::app::{AppCtx, View};
-use crate::{Binding, ClickState, adjust_brush, app::AppState, id, rect};
+use crate::{Binding, ClickState, Transition, adjust_brush, app::AppState, id, rect};
 use crate::{DEFAULT_FG, DEFAULT_GRAY, DEFAULT_LIGHT_GRAY, TRANSPARENT, circle};
 use backer::{
     Area, Layout,
@@ -7,6 +7,7 @@ use backer::{
 };
 use std::rc::Rc;
 use vello_svg::vello::peniko::Brush;
+use winit::window::CursorIcon;
 
 #[derive(Default, Debug, Clone, Copy)]
 pub struct ToggleState {
@@ -121,11 +122,12 @@ impl<'a, State> Toggle<'a, State> {
                     .offset(
                         {
                             let button_padding = height - (height * 0.5);
-                            if state.on {
+                            let offset = if state.on {
                                 (width * 0.5) - button_padding
                             } else {
                                 (-width * 0.5) + button_padding
-                            }
+                            };
+                            ctx.animate(id!(id), offset, Transition::spring(400., 30.))
                         },
                         0.,
                     )
@@ -137,6 +139,7 @@ impl<'a, State> Toggle<'a, State> {
                 rect(crate::id!(id))
                     .fill(TRANSPARENT)
                     .view()
+                    .cursor(CursorIcon::Pointer)
                     .on_hover({
                         let binding = self.binding.clone();
                         move |state: &mut State, _app: &mut AppState, h| {
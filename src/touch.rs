@@ -0,0 +1,641 @@
+use crate::app::{AppState, EditState};
+use crate::event::TouchEvent;
+use crate::gestures::{ClickLocation, Interaction, InteractionType};
+use crate::hit_test;
+use crate::input::{InputState, Placement};
+use crate::{
+    ClickState, DragState, GesturePhase, GestureState, Pan, Pinch, Point, Rotation, ZoomEvent,
+};
+use backer::Area;
+use std::f64::consts::PI;
+use winit::event::{MouseButton, TouchPhase};
+
+/// Progress of a two-finger gesture, delivered to the view under the gesture's start.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GestureSession {
+    target: u64,
+    scale: f64,
+    angle: f64,
+    translation: Point,
+}
+
+impl GestureSession {
+    fn new(target: u64) -> Self {
+        Self {
+            target,
+            scale: 1.,
+            angle: 0.,
+            translation: Point::ZERO,
+        }
+    }
+}
+
+/// Touch points and in-progress gestures of a window.
+#[derive(Debug, Default)]
+pub(crate) struct TouchState {
+    touches: Vec<(u64, Point)>,
+    /// Positions of the two fingers when the touch gesture began.
+    origin: Option<(Point, Point)>,
+    touch_session: Option<GestureSession>,
+    pinch_session: Option<GestureSession>,
+    rotation_session: Option<GestureSession>,
+    pan_session: Option<GestureSession>,
+}
+
+impl<State: 'static> InputState<State> {
+    /// A single finger drives the pointer; two fingers drive pinch, rotate and pan.
+    /// `event.position` is in logical coordinates.
+    pub(crate) fn touch(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        event: TouchEvent,
+    ) -> bool {
+        let mut needs_redraw = false;
+        match event.phase {
+            TouchPhase::Started => {
+                self.touch.touches.retain(|(id, _)| *id != event.id);
+                self.touch.touches.push((event.id, event.position));
+                match self.touch.touches.len() {
+                    1 => {
+                        needs_redraw |= self.mouse_moved(state, app_state, event.position);
+                        needs_redraw |= self.mouse_pressed(state, app_state, MouseButton::Left);
+                    }
+                    2 => {
+                        needs_redraw |= self.cancel_pointer(state, app_state);
+                        needs_redraw |= self.begin_touch_gesture(state, app_state);
+                    }
+                    _ => (),
+                }
+            }
+            TouchPhase::Moved => {
+                let Some(touch) = self
+                    .touch
+                    .touches
+                    .iter_mut()
+                    .find(|(id, _)| *id == event.id)
+                else {
+                    return false;
+                };
+                touch.1 = event.position;
+                match self.touch.touches.len() {
+                    1 => needs_redraw |= self.mouse_moved(state, app_state, event.position),
+                    2 => needs_redraw |= self.update_touch_gesture(state, app_state),
+                    _ => (),
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if !self.touch.touches.iter().any(|(id, _)| *id == event.id) {
+                    return false;
+                }
+                let phase = if event.phase == TouchPhase::Ended {
+                    GesturePhase::Ended
+                } else {
+                    GesturePhase::Cancelled
+                };
+                match self.touch.touches.len() {
+                    1 => {
+                        needs_redraw |= self.mouse_moved(state, app_state, event.position);
+                        needs_redraw |= if phase == GesturePhase::Ended {
+                            self.mouse_released(state, app_state, MouseButton::Left)
+                        } else {
+                            self.cancel_pointer(state, app_state)
+                        };
+                        // Fingers don't hover once lifted
+                        needs_redraw |= self.mouse_exited(state, app_state);
+                    }
+                    2 => needs_redraw |= self.end_touch_gesture(state, app_state, phase),
+                    // A finger of the gesture lifted, so start over with the two that remain
+                    _ if self.touch.touches[..2]
+                        .iter()
+                        .any(|(id, _)| *id == event.id) =>
+                    {
+                        needs_redraw |= self.end_touch_gesture(state, app_state, phase);
+                        self.touch.touches.retain(|(id, _)| *id != event.id);
+                        needs_redraw |= self.begin_touch_gesture(state, app_state);
+                    }
+                    _ => (),
+                }
+                self.touch.touches.retain(|(id, _)| *id != event.id);
+            }
+        }
+        needs_redraw
+    }
+
+    /// Ends a pointer press without completing a click, e.g. when a second finger lands.
+    fn cancel_pointer(&mut self, state: &mut State, app_state: &mut AppState) -> bool {
+        let GestureState::Dragging {
+            start,
+            last_position,
+            capturer,
+            button,
+        } = self.gesture_state
+        else {
+            return false;
+        };
+        self.gesture_state = GestureState::None;
+        self.long_pressed = false;
+        self.cancel_long_press();
+        if let Some(EditState { editor, .. }) = app_state.app_context.editor.as_mut() {
+            editor.mouse_released();
+        }
+        for (_, area, gh, placement) in self
+            .gesture_handlers
+            .clone()
+            .iter()
+            .filter(|(id, _, _, _)| *id == capturer)
+        {
+            let Some(ref handler) = gh.interaction_handler else {
+                continue;
+            };
+            if gh.interaction_type.clicks(button) {
+                handler(
+                    state,
+                    app_state,
+                    Interaction::Click(
+                        ClickState::Cancelled,
+                        ClickLocation::new(
+                            last_position,
+                            placement.local(area, last_position),
+                            button,
+                            self.click_counter.count,
+                        ),
+                    ),
+                );
+            }
+            if button == MouseButton::Left && gh.interaction_type.drag {
+                handler(
+                    state,
+                    app_state,
+                    Interaction::Drag(DragState::Completed {
+                        start: placement.local(area, start),
+                        current: placement.local(area, last_position),
+                        start_global: start,
+                        current_global: last_position,
+                        delta: Point::ZERO,
+                        distance: start.distance(last_position) as f32,
+                    }),
+                );
+            }
+        }
+        true
+    }
+
+    fn begin_touch_gesture(&mut self, state: &mut State, app_state: &mut AppState) -> bool {
+        let (a, b) = (self.touch.touches[0].1, self.touch.touches[1].1);
+        let center = a.midpoint(b);
+        self.touch.origin = Some((a, b));
+        self.touch.touch_session = self
+            .gesture_target(center, |t| t.pinch || t.rotate || t.pan || t.zoom)
+            .map(GestureSession::new);
+        let Some(session) = self.touch.touch_session else {
+            return false;
+        };
+        self.emit_session(
+            state,
+            app_state,
+            session,
+            session,
+            GesturePhase::Began,
+            center,
+        )
+    }
+
+    fn update_touch_gesture(&mut self, state: &mut State, app_state: &mut AppState) -> bool {
+        let (Some(previous), Some((start_a, start_b))) =
+            (self.touch.touch_session, self.touch.origin)
+        else {
+            return false;
+        };
+        let (a, b) = (self.touch.touches[0].1, self.touch.touches[1].1);
+        let start_distance = start_a.distance(start_b);
+        let session = GestureSession {
+            target: previous.target,
+            scale: if start_distance > 0. {
+                a.distance(b) / start_distance
+            } else {
+                1.
+            },
+            angle: normalize_angle((b - a).atan2() - (start_b - start_a).atan2()),
+            translation: (a.midpoint(b) - start_a.midpoint(start_b)).to_point(),
+        };
+        self.touch.touch_session = Some(session);
+        self.emit_session(
+            state,
+            app_state,
+            previous,
+            session,
+            GesturePhase::Changed,
+            a.midpoint(b),
+        )
+    }
+
+    fn end_touch_gesture(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        phase: GesturePhase,
+    ) -> bool {
+        self.touch.origin = None;
+        let Some(session) = self.touch.touch_session.take() else {
+            return false;
+        };
+        let center = self.touch.touches[0].1.midpoint(self.touch.touches[1].1);
+        self.emit_session(state, app_state, session, session, phase, center)
+    }
+
+    /// `delta` is the change in magnification reported by the trackpad.
+    pub(crate) fn trackpad_pinch(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        delta: f64,
+        phase: TouchPhase,
+    ) -> bool {
+        let Some(center) = self.cursor_position else {
+            return false;
+        };
+        let Some((previous, session, phase)) = self.advance_session(
+            phase,
+            center,
+            |touch| &mut touch.pinch_session,
+            |t| t.pinch || t.zoom,
+            |session| session.scale *= 1. + delta,
+        ) else {
+            return false;
+        };
+        let mut delivered = self.emit(
+            state,
+            app_state,
+            session.target,
+            |t| t.pinch,
+            |area, placement| {
+                Interaction::Pinch(Pinch {
+                    phase,
+                    scale: session.scale,
+                    delta: session.scale / previous.scale,
+                    center: placement.local(area, center),
+                })
+            },
+        );
+        delivered |= self.emit_zoom(
+            state,
+            app_state,
+            session.target,
+            phase,
+            previous,
+            session,
+            center,
+        );
+        delivered
+    }
+
+    /// `delta` is in degrees, counterclockwise, as reported by the trackpad.
+    pub(crate) fn trackpad_rotate(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        delta: f32,
+        phase: TouchPhase,
+    ) -> bool {
+        let Some(center) = self.cursor_position else {
+            return false;
+        };
+        let Some((previous, session, phase)) = self.advance_session(
+            phase,
+            center,
+            |touch| &mut touch.rotation_session,
+            |t| t.rotate,
+            |session| session.angle -= (delta as f64).to_radians(),
+        ) else {
+            return false;
+        };
+        self.emit(
+            state,
+            app_state,
+            session.target,
+            |t| t.rotate,
+            |area, placement| {
+                Interaction::Rotate(Rotation {
+                    phase,
+                    angle: session.angle,
+                    delta: session.angle - previous.angle,
+                    center: placement.local(area, center),
+                })
+            },
+        )
+    }
+
+    /// `delta` is in logical coordinates.
+    pub(crate) fn trackpad_pan(
+        &mut self,
+        state: &mut State,
+        app_state: &mut AppState,
+        delta: Point,
+        phase: TouchPhase,
+    ) -> bool {
+        let Some(center) = self.cursor_position else {
+            return false;
+        };
+        let Some((previous, session, phase)) = self.advance_session(
+            phase,
+            center,
+            |touch| &mut touch.pan_session,
+            |t| t.pan,
+            |session| session.translation += delta.to_vec2(),
+        ) else {
+            return false;
+        };
+        self.emit(
+            state,
+            app_state,
+            session.target,
+            |t| t.pan,
+            |area, placement| {
+                Interaction::Pan(Pan {
+                    phase,
+                    translation: session.translation,
+                    delta: (session.translation - previous.translation).to_point(),
+                    center: placement.local(area, center),
+                })
+            },
+        )
+    }
+
+    /// Starts, updates or ends a trackpad gesture session, returning the session before
+    /// and after `apply` along with the phase to report.
+    fn advance_session(
+        &mut self,
+        phase: TouchPhase,
+        center: Point,
+        session: impl Fn(&mut TouchState) -> &mut Option<GestureSession>,
+        wants: impl Fn(&InteractionType) -> bool,
+        apply: impl FnOnce(&mut GestureSession),
+    ) -> Option<(GestureSession, GestureSession, GesturePhase)> {
+        if phase == TouchPhase::Started {
+            let target = self.gesture_target(center, wants);
+            *session(&mut self.touch) = target.map(GestureSession::new);
+        }
+        let slot = session(&mut self.touch);
+        let previous = (*slot)?;
+        let mut current = previous;
+        apply(&mut current);
+        let phase = match phase {
+            TouchPhase::Started => GesturePhase::Began,
+            TouchPhase::Moved => GesturePhase::Changed,
+            TouchPhase::Ended => GesturePhase::Ended,
+            TouchPhase::Cancelled => GesturePhase::Cancelled,
+        };
+        *slot = match phase {
+            GesturePhase::Began | GesturePhase::Changed => Some(current),
+            GesturePhase::Ended | GesturePhase::Cancelled => None,
+        };
+        Some((previous, current, phase))
+    }
+
+    fn gesture_target(
+        &self,
+        point: Point,
+        wants: impl Fn(&InteractionType) -> bool,
+    ) -> Option<u64> {
+        self.gesture_handlers
+            .iter()
+            .rev()
+            .find(|(_, area, handler, placement)| {
+                hit_test(area, placement, point) && wants(&handler.interaction_type)
+            })
+            .map(|(id, _, _, _)| *id)
+    }
+
+    /// Reports a touch gesture to the pinch, rotate and pan handlers of its target.
+    fn emit_session(
+        &self,
+        state: &mut State,
+        app_state: &mut AppState,
+        previous: GestureSession,
+        session: GestureSession,
+        phase: GesturePhase,
+        center: Point,
+    ) -> bool {
+        let mut delivered = self.emit(
+            state,
+            app_state,
+            session.target,
+            |t| t.pinch,
+            |area, placement| {
+                Interaction::Pinch(Pinch {
+                    phase,
+                    scale: session.scale,
+                    delta: session.scale / previous.scale,
+                    center: placement.local(area, center),
+                })
+            },
+        );
+        delivered |= self.emit(
+            state,
+            app_state,
+            session.target,
+            |t| t.rotate,
+            |area, placement| {
+                Interaction::Rotate(Rotation {
+                    phase,
+                    angle: session.angle,
+                    delta: normalize_angle(session.angle - previous.angle),
+                    center: placement.local(area, center),
+                })
+            },
+        );
+        delivered |= self.emit_zoom(
+            state,
+            app_state,
+            session.target,
+            phase,
+            previous,
+            session,
+            center,
+        );
+        delivered |= self.emit(
+            state,
+            app_state,
+            session.target,
+            |t| t.pan,
+            |area, placement| {
+                Interaction::Pan(Pan {
+                    phase,
+                    translation: session.translation,
+                    delta: (session.translation - previous.translation).to_point(),
+                    center: placement.local(area, center),
+                })
+            },
+        );
+        delivered
+    }
+
+    fn emit_zoom(
+        &self,
+        state: &mut State,
+        app_state: &mut AppState,
+        target: u64,
+        phase: GesturePhase,
+        previous: GestureSession,
+        session: GestureSession,
+        center: Point,
+    ) -> bool {
+        self.emit(
+            state,
+            app_state,
+            target,
+            |t| t.zoom,
+            |area, placement| {
+                Interaction::Zoom(ZoomEvent {
+                    phase,
+                    delta: session.scale / previous.scale,
+                    focus: placement.local(area, center),
+                })
+            },
+        )
+    }
+
+    fn emit(
+        &self,
+        state: &mut State,
+        app_state: &mut AppState,
+        target: u64,
+        wants: impl Fn(&InteractionType) -> bool,
+        interaction: impl Fn(&Area, &Placement) -> Interaction,
+    ) -> bool {
+        let mut delivered = false;
+        for (id, area, gh, placement) in self.gesture_handlers.clone() {
+            if id == target
+                && wants(&gh.interaction_type)
+                && let Some(ref handler) = gh.interaction_handler
+            {
+                delivered = true;
+                handler(state, app_state, interaction(&area, &placement));
+            }
+        }
+        delivered
+    }
+}
+
+fn normalize_angle(angle: f64) -> f64 {
+    let angle = angle.rem_euclid(2. * PI);
+    if angle > PI { angle - 2. * PI } else { angle }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Default)]
+    struct State {
+        pans: Vec<Pan>,
+        zooms: Vec<ZoomEvent>,
+    }
+
+    fn view<'a>(_state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        rect(crate::id!())
+            .fill(TRANSPARENT)
+            .view()
+            .on_pan(|state: &mut State, _app, pan| state.pans.push(pan))
+            .finish(app.ctx())
+            .pad(50.)
+    }
+
+    #[test]
+    fn pan_center_is_relative_to_the_view() {
+        let mut app = TestApp::new(State::default(), view).size(200., 200.);
+        app.touch(0, TouchPhase::Started, 80., 100.);
+        app.touch(1, TouchPhase::Started, 120., 100.);
+        app.touch(1, TouchPhase::Moved, 130., 100.);
+        let pan = app.state.pans.last().unwrap();
+        assert_eq!(pan.phase, GesturePhase::Changed);
+        assert_eq!(pan.center, Point::new(55., 50.));
+        assert_eq!(pan.translation, Point::new(5., 0.));
+    }
+
+    #[test]
+    fn lifting_a_gesture_finger_restarts_from_the_remaining_two() {
+        let mut app = TestApp::new(State::default(), view).size(200., 200.);
+        app.touch(0, TouchPhase::Started, 80., 100.);
+        app.touch(1, TouchPhase::Started, 120., 100.);
+        app.touch(2, TouchPhase::Started, 100., 140.);
+        app.touch(0, TouchPhase::Ended, 80., 100.);
+        let phases: Vec<_> = app.state.pans.iter().map(|pan| pan.phase).collect();
+        assert_eq!(
+            phases,
+            [
+                GesturePhase::Began,
+                GesturePhase::Ended,
+                GesturePhase::Began
+            ]
+        );
+        app.touch(2, TouchPhase::Moved, 100., 150.);
+        let pan = app.state.pans.last().unwrap();
+        assert_eq!(pan.translation, Point::new(0., 5.));
+    }
+
+    fn zoom_view<'a>(_state: &'a State, app: &mut AppState) -> Layout<'a, View<State>, AppCtx> {
+        rect(crate::id!())
+            .fill(TRANSPARENT)
+            .view()
+            .on_zoom(|state: &mut State, _app, zoom| state.zooms.push(zoom))
+            .finish(app.ctx())
+            .pad(50.)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn ctrl_wheel_zooms_about_the_pointer() {
+        let mut app = TestApp::new(State::default(), zoom_view).size(200., 200.);
+        app.move_pointer(80., 100.);
+        app.scroll(0., 10.);
+        assert!(app.state.zooms.is_empty());
+        app.modifiers(ModifiersState::CONTROL);
+        app.scroll(0., 10.);
+        app.scroll(0., -20.);
+        let [zoom_in, zoom_out] = app.state.zooms.as_slice() else {
+            panic!("expected two zooms, got {:?}", app.state.zooms);
+        };
+        assert_eq!(zoom_in.phase, GesturePhase::Changed);
+        assert_close(zoom_in.delta, (10f64 * 0.01).exp());
+        assert_close(zoom_out.delta, (-20f64 * 0.01).exp());
+        assert_eq!(zoom_in.focus, Point::new(30., 50.));
+        assert_eq!(zoom_out.focus, Point::new(30., 50.));
+    }
+
+    #[test]
+    fn trackpad_pinch_zooms_about_the_pointer() {
+        let mut app = TestApp::new(State::default(), zoom_view).size(200., 200.);
+        app.move_pointer(120., 60.);
+        app.trackpad_pinch(0., TouchPhase::Started);
+        app.trackpad_pinch(0.1, TouchPhase::Moved);
+        app.trackpad_pinch(0.1, TouchPhase::Moved);
+        app.trackpad_pinch(0., TouchPhase::Ended);
+        let phases: Vec<_> = app.state.zooms.iter().map(|zoom| zoom.phase).collect();
+        assert_eq!(
+            phases,
+            [
+                GesturePhase::Began,
+                GesturePhase::Changed,
+                GesturePhase::Changed,
+                GesturePhase::Ended
+            ]
+        );
+        let deltas: Vec<_> = app.state.zooms.iter().map(|zoom| zoom.delta).collect();
+        // Each delta is relative to the previous event, so they multiply up to the total scale
+        assert_close(deltas[0], 1.);
+        assert_close(deltas[1], 1.1);
+        assert_close(deltas[2], 1.1);
+        assert_close(deltas[3], 1.);
+        for zoom in &app.state.zooms {
+            assert_eq!(zoom.focus, Point::new(70., 10.));
+        }
+    }
+}
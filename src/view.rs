@@ -1,16 +1,24 @@
 use crate::app::{AppCtx, AppState, View};
-use crate::gestures::{ClickLocation, Interaction, InteractionType, ScrollDelta};
+use crate::drag_drop::{DragItem, DropEvent, PreviewFn, scope_drag_item};
+use crate::gestures::{
+    ClickLocation, FileDragState, Interaction, InteractionType, Pan, Pinch, Rotation, ScrollDelta,
+    ZoomEvent,
+};
 use crate::image::Image;
 
 use crate::shape::PathData;
 use crate::svg::Svg;
 use crate::text::Text;
-use crate::{Binding, ClickState, DragState, GestureHandler, Key};
+use crate::{Binding, ClickState, DragState, GestureHandler, Insets, KeyEvent, Point};
 use backer::{Area, Layout, nodes::*};
 use parley::Layout as TextLayout;
+use std::any::TypeId;
+use std::path::PathBuf;
 use std::rc::Rc;
-use vello_svg::vello::kurbo::{Affine, BezPath};
-use vello_svg::vello::peniko::Brush;
+use std::time::Duration;
+use vello_svg::vello::kurbo::{Affine, BezPath, Rect, Shape as _};
+use vello_svg::vello::peniko::{BlendMode, Brush};
+use winit::window::CursorIcon;
 
 // A simple const FNV-1a hash for our purposes
 const FNV_OFFSET: u64 = 1469598103934665603;
@@ -97,6 +105,72 @@ pub fn clipping<'a, State>(
     ])
 }
 
+/// Draws `content` with its opacity multiplied by `alpha`, as a single group so overlapping
+/// views don't show through each other.
+pub fn opacity<'a, State>(
+    alpha: f32,
+    content: Layout<'a, View<State>, AppCtx>,
+) -> Layout<'a, View<State>, AppCtx> {
+    layer(alpha, BlendMode::default(), content)
+}
+
+/// Blends `content` with what is drawn below it using `mode`, e.g. `Mix::Multiply`.
+pub fn blend<'a, State>(
+    mode: impl Into<BlendMode>,
+    content: Layout<'a, View<State>, AppCtx>,
+) -> Layout<'a, View<State>, AppCtx> {
+    layer(1., mode.into(), content)
+}
+
+fn layer<'a, State>(
+    alpha: f32,
+    blend: BlendMode,
+    content: Layout<'a, View<State>, AppCtx>,
+) -> Layout<'a, View<State>, AppCtx> {
+    stack(vec![
+        draw(move |area, _| {
+            vec![View::PushLayer {
+                path: Rect::new(
+                    area.x as f64,
+                    area.y as f64,
+                    (area.x + area.width) as f64,
+                    (area.y + area.height) as f64,
+                )
+                .to_path(0.1),
+                alpha,
+                blend,
+            }]
+        }),
+        content,
+        draw(|_, _| vec![View::PopLayer]),
+    ])
+}
+
+/// Applies `transform` to everything drawn by `content`, including its hit testing.
+/// `anchor` is the point the transform is applied about, as a fraction of the content's
+/// area, so `Point::new(0.5, 0.5)` rotates or scales it about its center.
+pub fn transform<'a, State>(
+    transform: Affine,
+    anchor: Point,
+    content: Layout<'a, View<State>, AppCtx>,
+) -> Layout<'a, View<State>, AppCtx> {
+    stack(vec![
+        draw(move |area, _| {
+            let anchor = (
+                area.x as f64 + area.width as f64 * anchor.x,
+                area.y as f64 + area.height as f64 * anchor.y,
+            );
+            vec![View::PushTransform {
+                transform: Affine::translate(anchor)
+                    * transform
+                    * Affine::translate((-anchor.0, -anchor.1)),
+            }]
+        }),
+        content,
+        draw(|_, _| vec![View::PopTransform]),
+    ])
+}
+
 pub struct Drawable<State> {
     pub(crate) view_type: DrawableType,
     pub(crate) gesture_handlers: Vec<GestureHandler<State, AppState>>,
@@ -141,6 +215,54 @@ impl<State> Drawable<State> {
         });
         self
     }
+    /// Called when a second click in quick succession completes.
+    pub fn on_double_click(
+        self,
+        f: impl Fn(&mut State, &mut AppState, ClickLocation) + 'static,
+    ) -> Self {
+        self.on_click(move |state, app, click, location| {
+            if click == ClickState::Completed && location.count() == 2 {
+                f(state, app, location)
+            }
+        })
+    }
+    /// Called for right clicks, e.g. to open a context menu.
+    pub fn on_secondary_click(
+        mut self,
+        f: impl Fn(&mut State, &mut AppState, ClickState, ClickLocation) + 'static,
+    ) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                secondary_click: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::Click(click, location) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, click, location);
+            })),
+        });
+        self
+    }
+    pub fn on_middle_click(
+        mut self,
+        f: impl Fn(&mut State, &mut AppState, ClickState, ClickLocation) + 'static,
+    ) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                middle_click: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::Click(click, location) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, click, location);
+            })),
+        });
+        self
+    }
     pub fn on_click_outside(
         mut self,
         f: impl Fn(&mut State, &mut AppState, ClickState, ClickLocation) + 'static,
@@ -189,21 +311,111 @@ impl<State> Drawable<State> {
         });
         self
     }
-    pub fn on_key(mut self, f: impl Fn(&mut State, &mut AppState, Key) + 'static) -> Self {
+    /// Called once the pointer has been held down on this view for `duration` without moving.
+    /// A click released after a long press is cancelled rather than completed.
+    pub fn on_long_press(
+        mut self,
+        duration: Duration,
+        f: impl Fn(&mut State, &mut AppState, ClickLocation) + 'static,
+    ) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                long_press: Some(duration),
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::LongPress(location) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, location);
+            })),
+        });
+        self
+    }
+    /// Called with `true` once the pointer has rested on this view for `delay`, and with
+    /// `false` when it leaves afterwards. Useful for tooltips.
+    pub fn on_hover_intent(
+        mut self,
+        delay: Duration,
+        f: impl Fn(&mut State, &mut AppState, bool) + 'static,
+    ) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                hover_intent: Some(delay),
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::HoverIntent(intent) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, intent);
+            })),
+        });
+        self
+    }
+    /// Called for key presses and releases while this view has focus. Views with key handlers are focusable.
+    pub fn on_key(mut self, f: impl Fn(&mut State, &mut AppState, KeyEvent) + 'static) -> Self {
         self.gesture_handlers.push(GestureHandler {
             interaction_type: InteractionType {
                 key: true,
                 ..Default::default()
             },
             interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
-                let Interaction::Key(key) = interaction else {
+                let Interaction::Key(event) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, event);
+            })),
+        });
+        self
+    }
+    /// Called for every key press and release, regardless of focus.
+    pub fn on_global_key(
+        mut self,
+        f: impl Fn(&mut State, &mut AppState, KeyEvent) + 'static,
+    ) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                global_key: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::Key(event) = interaction else {
                     return;
                 };
-                (f)(state, app_state, key);
+                (f)(state, app_state, event);
             })),
         });
         self
     }
+    /// Called with `true` when this view gains focus and `false` when it loses it.
+    pub fn on_focus(mut self, f: impl Fn(&mut State, &mut AppState, bool) + 'static) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                focus: true,
+                focusable: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::Focus(focused) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, focused);
+            })),
+        });
+        self
+    }
+    /// Makes the view a Tab stop that takes focus when clicked.
+    pub fn focusable(mut self) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                focusable: true,
+                ..Default::default()
+            },
+            interaction_handler: None,
+        });
+        self
+    }
     pub fn on_scroll(
         mut self,
         f: impl Fn(&mut State, &mut AppState, ScrollDelta) + 'static,
@@ -222,6 +434,143 @@ impl<State> Drawable<State> {
         });
         self
     }
+    pub fn on_pinch(mut self, f: impl Fn(&mut State, &mut AppState, Pinch) + 'static) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                pinch: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::Pinch(pinch) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, pinch);
+            })),
+        });
+        self
+    }
+    pub fn on_rotate(mut self, f: impl Fn(&mut State, &mut AppState, Rotation) + 'static) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                rotate: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::Rotate(rotation) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, rotation);
+            })),
+        });
+        self
+    }
+    /// Called for pinches and Ctrl+wheel, which then no longer scrolls this view.
+    pub fn on_zoom(mut self, f: impl Fn(&mut State, &mut AppState, ZoomEvent) + 'static) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                zoom: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::Zoom(zoom) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, zoom);
+            })),
+        });
+        self
+    }
+    pub fn on_pan(mut self, f: impl Fn(&mut State, &mut AppState, Pan) + 'static) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                pan: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::Pan(pan) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, pan);
+            })),
+        });
+        self
+    }
+    /// Shows `icon` while the pointer is over the view, or while the view is being dragged,
+    /// e.g. `CursorIcon::ColResize` for a split view divider.
+    pub fn cursor(mut self, icon: CursorIcon) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                cursor: Some(icon),
+                ..Default::default()
+            },
+            interaction_handler: None,
+        });
+        self
+    }
+    /// Grows the area in which a press is caught by the view when no view is under the
+    /// pointer exactly, and shrinks the area counted as outside by `on_click_outside`.
+    /// Defaults to `AppState::set_hit_slop`.
+    pub fn hit_slop(mut self, insets: impl Into<Insets>) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                hit_slop: Some(insets.into()),
+                ..Default::default()
+            },
+            interaction_handler: None,
+        });
+        self
+    }
+    /// Lets hover, scroll and file drops over this view continue to the views below it,
+    /// unless a handler calls `AppState::stop_propagation`. Presses are still captured
+    /// by a single view.
+    pub fn pass_through(mut self) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                pass_through: true,
+                ..Default::default()
+            },
+            interaction_handler: None,
+        });
+        self
+    }
+    /// Called while files dragged from the OS are over the view.
+    pub fn on_file_hover(
+        mut self,
+        f: impl Fn(&mut State, &mut AppState, FileDragState) + 'static,
+    ) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                file_hover: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::FileHover(drag) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, drag);
+            })),
+        });
+        self
+    }
+    /// Called with every file of a drop and the drop position in window coordinates.
+    pub fn on_file_drop(
+        mut self,
+        f: impl Fn(&mut State, &mut AppState, Vec<PathBuf>, Point) + 'static,
+    ) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                file_drop: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::FileDrop(paths, position) = interaction else {
+                    return;
+                };
+                (f)(state, app_state, paths, position);
+            })),
+        });
+        self
+    }
 }
 
 impl DrawableType {
@@ -237,6 +586,66 @@ impl DrawableType {
 }
 
 impl<State: 'static> Drawable<State> {
+    /// Makes the view draggable onto `drop_target` views accepting `T`.
+    ///
+    /// `payload` is called once the pointer has moved a few pixels from a press, and
+    /// `preview` is drawn under the pointer, at the size of this view, until release.
+    pub fn drag_source<T: 'static>(
+        mut self,
+        payload: impl Fn(&State) -> T + 'static,
+        preview: impl Fn(&State, &mut AppCtx) -> Layout<'static, View<State>, AppCtx> + 'static,
+    ) -> Self {
+        let preview: PreviewFn<State> = Rc::new(preview);
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                drag_source: true,
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, _, interaction| {
+                let Interaction::DragSource(slot) = interaction else {
+                    return;
+                };
+                *slot.borrow_mut() = Some(DragItem {
+                    payload: Rc::new(payload(state)),
+                    type_id: TypeId::of::<T>(),
+                    preview: Box::new(preview.clone()),
+                });
+            })),
+        });
+        self
+    }
+    /// Accepts drags from `drag_source` views whose payload is a `T`.
+    ///
+    /// `on_enter` is called with `true` when such a drag moves over the view and `false`
+    /// when it leaves or is dropped. `on_over` and `on_drop` receive the pointer position
+    /// relative to the view.
+    pub fn drop_target<T: Clone + 'static>(
+        mut self,
+        on_enter: impl Fn(&mut State, &mut AppState, bool) + 'static,
+        on_over: impl Fn(&mut State, &mut AppState, &T, Point) + 'static,
+        on_drop: impl Fn(&mut State, &mut AppState, T, Point) + 'static,
+    ) -> Self {
+        self.gesture_handlers.push(GestureHandler {
+            interaction_type: InteractionType {
+                drop_target: Some(TypeId::of::<T>()),
+                ..Default::default()
+            },
+            interaction_handler: Some(Rc::new(move |state, app_state, interaction| {
+                let Interaction::DropTarget(event, payload) = interaction else {
+                    return;
+                };
+                let Some(payload) = payload.downcast_ref::<T>() else {
+                    return;
+                };
+                match event {
+                    DropEvent::Enter(entered) => on_enter(state, app_state, entered),
+                    DropEvent::Over(point) => on_over(state, app_state, payload, point),
+                    DropEvent::Drop(point) => on_drop(state, app_state, payload.clone(), point),
+                }
+            })),
+        });
+        self
+    }
     pub fn finish<'a>(self, ctx: &mut AppCtx) -> Layout<'a, View<State>, AppCtx> {
         let text_clone = if let DrawableType::Text(t) = &self.view_type {
             Some(t.clone())
@@ -279,9 +688,16 @@ pub fn scope<'a, Root: 'static, Sub: 'static>(
                         let binding = binding.clone();
                         Rc::new(
                             move |root: &mut Root, app: &mut AppState, interaction: Interaction| {
+                                let slot = match &interaction {
+                                    Interaction::DragSource(slot) => Some(slot.clone()),
+                                    _ => None,
+                                };
                                 let mut sub = binding.get(root);
                                 h(&mut sub, app, interaction);
                                 binding.set(root, sub);
+                                if let Some(slot) = slot {
+                                    scope_drag_item(&slot, binding.clone());
+                                }
                             },
                         )
                             as Rc<dyn Fn(&mut Root, &mut AppState, Interaction)>
@@ -296,6 +712,10 @@ pub fn scope<'a, Root: 'static, Sub: 'static>(
         },
         View::PushClip { path } => View::PushClip { path },
         View::PopClip => View::PopClip,
+        View::PushLayer { path, alpha, blend } => View::PushLayer { path, alpha, blend },
+        View::PopLayer => View::PopLayer,
+        View::PushTransform { transform } => View::PushTransform { transform },
+        View::PopTransform => View::PopTransform,
         View::EditorArea(id, area) => View::EditorArea(id, area),
         View::Empty => View::Empty,
     })